@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    outline::Interval,
+    point::Point,
+    provenance::{DecomposedRect, SideKind},
+};
+
+/// Two rects of a decomposition that share (part of) a cut.
+///
+/// Cuts made by the horizontal scanline are horizontal, so the rect `below`
+/// has its top, and the rect `above` its bottom, on the scanline `y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adjacency {
+    pub below: usize,
+    pub above: usize,
+    pub y: isize,
+    /// The stretch of the scanline shared by both rects.
+    pub interval: Interval,
+}
+
+/// What each side of a decomposed rect lies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RectSides {
+    pub left: SideKind,
+    pub right: SideKind,
+    pub top: SideKind,
+    pub bottom: SideKind,
+}
+
+/// The rects of a decomposition, and which of them share a cut.
+#[derive(Clone, Debug, Default)]
+pub struct RectGraph {
+    pub rects: Vec<DecomposedRect>,
+    pub adjacencies: Vec<Adjacency>,
+}
+
+impl RectGraph {
+    /// Build the graph by walking the scanlines the decomposition stopped at.
+    /// A rect is emitted when the scanline reaches its top, so at each
+    /// scanline the rects closed there are matched, from left to right,
+    /// against the rects opened there.
+    pub fn new(rects: Vec<DecomposedRect>) -> Self {
+        let mut closed: BTreeMap<isize, Vec<usize>> = BTreeMap::new();
+        let mut opened: BTreeMap<isize, Vec<usize>> = BTreeMap::new();
+        for (ix, decomposed) in rects.iter().enumerate() {
+            let rect = decomposed.rect;
+            if rect.is_degenerate() {
+                continue;
+            }
+            closed.entry(rect.y_max()).or_default().push(ix);
+            opened.entry(rect.y_min()).or_default().push(ix);
+        }
+
+        let mut adjacencies = Vec::new();
+        for (y, mut below) in closed {
+            let Some(mut above) = opened.remove(&y) else {
+                continue;
+            };
+            below.sort_by_key(|&ix| rects[ix].rect.x_min());
+            above.sort_by_key(|&ix| rects[ix].rect.x_min());
+
+            let (mut i, mut j) = (0, 0);
+            while i < below.len() && j < above.len() {
+                let (lower, upper) =
+                    (rects[below[i]].rect, rects[above[j]].rect);
+                let start = lower.x_min().max(upper.x_min());
+                let end = lower.x_max().min(upper.x_max());
+                if start < end {
+                    adjacencies.push(Adjacency {
+                        below: below[i],
+                        above: above[j],
+                        y,
+                        interval: (start, end),
+                    });
+                }
+                if lower.x_max() < upper.x_max() {
+                    i += 1;
+                } else {
+                    j += 1;
+                }
+            }
+        }
+
+        Self { rects, adjacencies }
+    }
+
+    /// The adjacencies the rect at index `ix` takes part in.
+    pub fn neighbours(&self, ix: usize) -> impl Iterator<Item = &Adjacency> {
+        self.adjacencies
+            .iter()
+            .filter(move |adj| adj.below == ix || adj.above == ix)
+    }
+
+    /// Classify every side of the rect at index `ix`. Left and right walls
+    /// always lie on polygon edges, since the scanline only cuts
+    /// horizontally.
+    pub fn sides(&self, ix: usize) -> RectSides {
+        let decomposed = &self.rects[ix];
+        RectSides {
+            left: SideKind::Boundary,
+            right: SideKind::Boundary,
+            top: decomposed.top,
+            bottom: decomposed.bottom,
+        }
+    }
+}
+
+impl Decomposer {
+    /// Decompose a polygon, and return its rects along with which of them
+    /// share a cut.
+    pub fn decompose_graph(points: Vec<Point>) -> Result<RectGraph, DecompErr> {
+        Self::decompose_with_provenance(points).map(RectGraph::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rect::Rect;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    fn index_of(graph: &RectGraph, rect: Rect) -> usize {
+        graph
+            .rects
+            .iter()
+            .position(|decomposed| decomposed.rect == rect)
+            .unwrap()
+    }
+
+    #[test]
+    fn staircase_rects_share_cuts() {
+        let graph = Decomposer::decompose_graph(ring(&[
+            (0, 0),
+            (0, 30),
+            (3, 30),
+            (3, 20),
+            (2, 20),
+            (2, 10),
+            (1, 10),
+            (1, 0),
+        ]))
+        .unwrap();
+        let low = index_of(&graph, Rect::from_bounds(0, 0, 1, 10));
+        let middle = index_of(&graph, Rect::from_bounds(0, 10, 2, 20));
+        let high = index_of(&graph, Rect::from_bounds(0, 20, 3, 30));
+
+        let mut adjacencies = graph.adjacencies.clone();
+        adjacencies.sort_by_key(|adjacency| adjacency.y);
+        assert_eq!(
+            adjacencies,
+            vec![
+                Adjacency {
+                    below: low,
+                    above: middle,
+                    y: 10,
+                    interval: (0, 1),
+                },
+                Adjacency {
+                    below: middle,
+                    above: high,
+                    y: 20,
+                    interval: (0, 2),
+                },
+            ]
+        );
+        assert_eq!(graph.neighbours(middle).count(), 2);
+        assert_eq!(graph.neighbours(low).count(), 1);
+        assert_eq!(
+            graph.sides(low),
+            RectSides {
+                left: SideKind::Boundary,
+                right: SideKind::Boundary,
+                top: SideKind::Cut,
+                bottom: SideKind::Boundary,
+            }
+        );
+    }
+
+    #[test]
+    fn l_shape_has_one_cut() {
+        let graph = Decomposer::decompose_graph(ring(&[
+            (0, 0),
+            (0, 2),
+            (1, 2),
+            (1, 1),
+            (2, 1),
+            (2, 0),
+        ]))
+        .unwrap();
+        assert_eq!(graph.rects.len(), 2);
+        assert_eq!(graph.adjacencies.len(), 1);
+        assert_eq!(graph.adjacencies[0].interval, (0, 1));
+    }
+}
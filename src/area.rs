@@ -0,0 +1,178 @@
+use crate::{
+    outline::{ring_area, Outline},
+    point::Point,
+    rect::Rect,
+};
+
+/// What an area violation was measured on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AreaKind {
+    /// The region of an outline, holes excluded.
+    Polygon,
+    /// One of the holes of an outline, by index into its `holes`.
+    Hole(usize),
+}
+
+/// Minimum area rules, as areas in squared database units. A rule of zero is
+/// never violated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AreaRule {
+    pub min_area: i128,
+    /// Minimum area of a hole (enclosed area).
+    pub min_enclosed_area: i128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AreaViolation {
+    /// Index of the outline in the checked batch.
+    pub polygon: usize,
+    pub kind: AreaKind,
+    pub area: i128,
+    pub required: i128,
+    /// Bounding rect of the offending polygon or hole.
+    pub marker: Rect,
+}
+
+impl AreaRule {
+    /// Check every outline of a batch, and every hole of each.
+    pub fn check(&self, outlines: &[Outline]) -> Vec<AreaViolation> {
+        let mut violations = Vec::new();
+        for (polygon, outline) in outlines.iter().enumerate() {
+            // An area too large for an `i128` meets any rule.
+            if let Some(area) =
+                outline.area().filter(|&area| area < self.min_area)
+            {
+                violations.push(AreaViolation {
+                    polygon,
+                    kind: AreaKind::Polygon,
+                    area,
+                    required: self.min_area,
+                    marker: Rect::bounding(&outline.outer),
+                });
+            }
+            for (ix, hole) in outline.holes.iter().enumerate() {
+                if let Some(area) = ring_area(hole)
+                    .filter(|&area| area < self.min_enclosed_area)
+                {
+                    violations.push(AreaViolation {
+                        polygon,
+                        kind: AreaKind::Hole(ix),
+                        area,
+                        required: self.min_enclosed_area,
+                        marker: Rect::bounding(hole),
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Check a batch of polygons without holes, given as points.
+    pub fn check_polygons(
+        &self,
+        polygons: &[Vec<Point>],
+    ) -> Vec<AreaViolation> {
+        polygons
+            .iter()
+            .enumerate()
+            .filter_map(|(polygon, points)| {
+                let area = ring_area(points)?;
+                (area < self.min_area).then(|| AreaViolation {
+                    polygon,
+                    kind: AreaKind::Polygon,
+                    area,
+                    required: self.min_area,
+                    marker: Rect::bounding(points),
+                })
+            })
+            .collect()
+    }
+
+    /// Check a layer given as rects, such as the decompositions of its
+    /// polygons: the rects are first merged into outlines with
+    /// `Outline::from_rects`, which also brings out the holes they enclose.
+    /// Violations index into the returned outlines.
+    pub fn check_rects(
+        &self,
+        rects: &[Rect],
+    ) -> (Vec<Outline>, Vec<AreaViolation>) {
+        let outlines = Outline::from_rects(rects);
+        let violations = self.check(&outlines);
+        (outlines, violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four rects around a 1 by 1 hole.
+    fn frame() -> Vec<Rect> {
+        vec![
+            Rect::from_bounds(0, 0, 3, 1),
+            Rect::from_bounds(0, 2, 3, 3),
+            Rect::from_bounds(0, 1, 1, 2),
+            Rect::from_bounds(2, 1, 3, 2),
+        ]
+    }
+
+    #[test]
+    fn polygons() {
+        let l = [(0, 0), (0, 2), (1, 2), (1, 1), (2, 1), (2, 0)]
+            .map(Point::from)
+            .to_vec();
+        let mut reversed = l.clone();
+        reversed.reverse();
+        let big = [(0, 0), (0, 2), (2, 2), (2, 0)].map(Point::from).to_vec();
+        let rule = AreaRule {
+            min_area: 4,
+            min_enclosed_area: 0,
+        };
+        let violations = rule.check_polygons(&[l, big, reversed]);
+        assert_eq!(
+            violations,
+            [0, 2].map(|polygon| AreaViolation {
+                polygon,
+                kind: AreaKind::Polygon,
+                area: 3,
+                required: 4,
+                marker: Rect::from_bounds(0, 0, 2, 2),
+            })
+        );
+        assert!(AreaRule::default().check_polygons(&[vec![]]).is_empty());
+    }
+
+    #[test]
+    fn rects_and_holes() {
+        let rule = AreaRule {
+            min_area: 9,
+            min_enclosed_area: 2,
+        };
+        let (outlines, violations) = rule.check_rects(&frame());
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(
+            violations,
+            vec![
+                AreaViolation {
+                    polygon: 0,
+                    kind: AreaKind::Polygon,
+                    area: 8,
+                    required: 9,
+                    marker: Rect::from_bounds(0, 0, 3, 3),
+                },
+                AreaViolation {
+                    polygon: 0,
+                    kind: AreaKind::Hole(0),
+                    area: 1,
+                    required: 2,
+                    marker: Rect::from_bounds(1, 1, 2, 2),
+                },
+            ]
+        );
+        let lenient = AreaRule {
+            min_area: 8,
+            min_enclosed_area: 1,
+        };
+        assert!(lenient.check(&outlines).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    outline::{combine, covered_bands, scanlines, Interval},
+    point::Point,
+    rect::Rect,
+};
+
+/// A boolean operation between two rectilinear regions `a` and `b`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    /// `a` minus `b`.
+    Difference,
+    Xor,
+}
+
+impl BooleanOp {
+    #[inline]
+    fn keep(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOp::Union => in_a || in_b,
+            BooleanOp::Intersection => in_a && in_b,
+            BooleanOp::Difference => in_a && !in_b,
+            BooleanOp::Xor => in_a != in_b,
+        }
+    }
+
+    /// Apply the operation to two sets of rects. Rects within a set may
+    /// overlap.
+    ///
+    /// The result is a set of non-overlapping rects: the same horizontal
+    /// scanline bands are swept over both sets, their covered intervals are
+    /// combined band by band, and identical intervals in consecutive bands
+    /// are merged into a single rect.
+    pub fn apply(self, a: &[Rect], b: &[Rect]) -> Vec<Rect> {
+        let ys = scanlines(a.iter().chain(b));
+        let bands = covered_bands(a, &ys)
+            .iter()
+            .zip(covered_bands(b, &ys).iter())
+            .map(|(in_a, in_b)| {
+                combine(in_a, in_b, |in_a, in_b| self.keep(in_a, in_b))
+            })
+            .collect::<Vec<Vec<Interval>>>();
+        rects_from_bands(&bands, &ys)
+    }
+
+    /// Apply the operation to two polygons, given as clockwise points, by
+    /// first decomposing each of them into rects.
+    pub fn apply_polygons(
+        self,
+        a: Vec<Point>,
+        b: Vec<Point>,
+    ) -> Result<Vec<Rect>, DecompErr> {
+        Ok(self.apply(&Decomposer::decompose(a)?, &Decomposer::decompose(b)?))
+    }
+}
+
+/// Turn the covered intervals of each band between consecutive `scanlines`
+/// into non-overlapping rects, extending a rect upwards for as long as the
+/// bands above it cover exactly the same interval.
+pub(crate) fn rects_from_bands(
+    bands: &[Vec<Interval>],
+    scanlines: &[isize],
+) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    // Maps each interval covered by the previous band to the scanline at
+    // which it started being covered.
+    let mut open: BTreeMap<Interval, isize> = BTreeMap::new();
+    for (intervals, band) in bands.iter().zip(scanlines.windows(2)) {
+        let bottom = band[0];
+        let mut next = BTreeMap::new();
+        for &interval in intervals {
+            let start = open.remove(&interval).unwrap_or(bottom);
+            next.insert(interval, start);
+        }
+        close(&mut rects, open, bottom);
+        open = next;
+    }
+    if let Some(&top) = scanlines.last() {
+        close(&mut rects, open, top);
+    }
+    rects
+}
+
+fn close(rects: &mut Vec<Rect>, open: BTreeMap<Interval, isize>, y_max: isize) {
+    rects.extend(open.into_iter().map(|((x_min, x_max), y_min)| {
+        Rect::from_bounds(x_min, y_min, x_max, y_max)
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(rects: &[Rect]) -> i128 {
+        rects.iter().map(Rect::area).sum()
+    }
+
+    fn overlap(a: &Rect, b: &Rect) -> bool {
+        a.x_min() < b.x_max()
+            && b.x_min() < a.x_max()
+            && a.y_min() < b.y_max()
+            && b.y_min() < a.y_max()
+    }
+
+    #[test]
+    fn areas() {
+        let a = [Rect::from_bounds(0, 0, 4, 4)];
+        let b = [Rect::from_bounds(1, 1, 3, 3), Rect::from_bounds(2, 2, 5, 5)];
+        for (op, expected) in [
+            (BooleanOp::Union, 21),
+            (BooleanOp::Intersection, 7),
+            (BooleanOp::Difference, 9),
+            (BooleanOp::Xor, 14),
+        ] {
+            let rects = op.apply(&a, &b);
+            assert_eq!(area(&rects), expected, "{:?}", op);
+            for (ix, rect) in rects.iter().enumerate() {
+                assert!(rects[ix + 1..]
+                    .iter()
+                    .all(|other| !overlap(rect, other)));
+            }
+        }
+    }
+
+    #[test]
+    fn identical_bands_merge() {
+        let rects = BooleanOp::Union.apply(
+            &[Rect::from_bounds(0, 0, 2, 1)],
+            &[Rect::from_bounds(0, 1, 2, 3)],
+        );
+        assert_eq!(rects, vec![Rect::from_bounds(0, 0, 2, 3)]);
+    }
+
+    #[test]
+    fn polygons() {
+        let l = [(0, 0), (0, 4), (2, 4), (2, 2), (4, 2), (4, 0)]
+            .map(Point::from)
+            .to_vec();
+        let square = [(0, 0), (0, 2), (2, 2), (2, 0)].map(Point::from).to_vec();
+        let rects = BooleanOp::Difference.apply_polygons(l, square).unwrap();
+        assert_eq!(area(&rects), 8);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    fracture::balanced_cuts,
+    polygon::Polygon,
+    rect::Rect,
+    sliver::cut_vertically,
+};
+
+impl Decomposer {
+    /// Decompose a polygon into rects at most `max_width` wide and
+    /// `max_height` tall, as for wide-metal slotting or fill. Returns
+    /// `NonPositiveBound` unless both bounds are positive.
+    ///
+    /// The cuts are placed for the bounds, rather than added to an ordinary
+    /// decomposition. The polygon is first cut into columns, with vertical
+    /// cuts only, where its vertical extent changes, so that no column is
+    /// split by a horizontal cut the bounds do not call for. Each column is
+    /// then cut into as few strips as fit `max_width`, of as uniform widths
+    /// as integer coordinates allow, and all of its strips into the same
+    /// rows, as few as fit `max_height` and as uniform. The rects of a column
+    /// thus form a grid, sharing every cut with their neighbours.
+    pub fn decompose_bounded(
+        polygon: impl Into<Polygon>,
+        max_width: isize,
+        max_height: isize,
+    ) -> Result<Vec<Rect>, DecompErr> {
+        if max_width <= 0 || max_height <= 0 {
+            return Err(DecompErr::NonPositiveBound);
+        }
+        let columns = cut_vertically(&Decomposer::decompose(polygon)?);
+        Ok(columns
+            .iter()
+            .flat_map(|column| {
+                let xs =
+                    balanced_cuts(column.x_min(), column.x_max(), max_width, 1);
+                let ys = balanced_cuts(
+                    column.y_min(),
+                    column.y_max(),
+                    max_height,
+                    1,
+                );
+                ys.windows(2)
+                    .flat_map(|row| {
+                        xs.windows(2).map(move |strip| {
+                            Rect::from_bounds(
+                                strip[0], row[0], strip[1], row[1],
+                            )
+                        })
+                    })
+                    .collect::<Vec<Rect>>()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// An L whose 5-wide arm rises from y = 3 to 9 over the left half of its
+    /// foot.
+    fn l_shape() -> Vec<Point> {
+        ring(&[(0, 0), (0, 9), (5, 9), (5, 3), (10, 3), (10, 0)])
+    }
+
+    #[test]
+    fn rects_fit_the_bounds() {
+        for (max_width, max_height) in [(1, 1), (3, 2), (4, 100), (100, 4)] {
+            let rects =
+                Decomposer::decompose_bounded(l_shape(), max_width, max_height)
+                    .unwrap();
+            assert_eq!(rects.iter().map(Rect::area).sum::<i128>(), 60);
+            assert!(rects.iter().all(|rect| {
+                rect.width() <= max_width && rect.height() <= max_height
+            }));
+        }
+        assert_eq!(
+            Decomposer::decompose_bounded(l_shape(), 1, 1)
+                .unwrap()
+                .len(),
+            60
+        );
+    }
+
+    #[test]
+    fn rows_are_balanced_over_whole_columns() {
+        let rects = Decomposer::decompose_bounded(l_shape(), 5, 7).unwrap();
+        let mut left = rects
+            .iter()
+            .filter(|rect| rect.x_min() == 0)
+            .map(|rect| (rect.y_min(), rect.y_max()))
+            .collect::<Vec<(isize, isize)>>();
+        left.sort_unstable();
+        // Not 0..3 and 3..9, where the scanline cuts the column.
+        assert_eq!(left, [(0, 5), (5, 9)]);
+        assert_eq!(rects.len(), 3);
+    }
+
+    #[test]
+    fn cuts_are_shared_within_columns() {
+        // The scanline would cut this into a 10 wide foot and a 7 wide top,
+        // whose cuts to a width of 5 would not line up.
+        let step = ring(&[(0, 0), (0, 4), (7, 4), (7, 2), (10, 2), (10, 0)]);
+        let mut rects = Decomposer::decompose_bounded(step, 5, 10).unwrap();
+        rects.sort_unstable_by_key(|rect| (rect.x_min(), rect.y_min()));
+        assert_eq!(
+            rects,
+            [
+                Rect::from_bounds(0, 0, 4, 4),
+                Rect::from_bounds(4, 0, 7, 4),
+                Rect::from_bounds(7, 0, 10, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn bounds_must_be_positive() {
+        for (max_width, max_height) in [(0, 5), (5, 0), (-1, 5), (5, -3)] {
+            assert_eq!(
+                Decomposer::decompose_bounded(l_shape(), max_width, max_height),
+                Err(DecompErr::NonPositiveBound)
+            );
+        }
+    }
+}
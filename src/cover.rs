@@ -0,0 +1,180 @@
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    geometry::Geometry,
+    grid::CompressedGrid,
+    point::Point,
+    rect::Rect,
+};
+
+/// Which maximal rects to keep in a cover.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoverFilter {
+    pub min_width: isize,
+    pub min_height: isize,
+}
+
+impl CoverFilter {
+    #[inline]
+    pub fn accepts(&self, rect: &Rect) -> bool {
+        rect.width() >= self.min_width && rect.height() >= self.min_height
+    }
+}
+
+/// Every maximal rect of inside cells of `grid`: one that cannot grow in any
+/// direction without leaving the polygon.
+///
+/// For each bottom row, rows are added on top one at a time while tracking
+/// which columns are still inside all the way up; each run of such columns
+/// is a rect that cannot grow sideways, and it is maximal if the rows just
+/// below and above it are not entirely inside.
+pub(crate) fn maximal_rects(grid: &CompressedGrid) -> Vec<Rect> {
+    let (columns, rows) = (grid.columns(), grid.rows());
+    // outside[row][column] counts the outside cells of `row` left of
+    // `column`, so that a stretch of a row can be checked in constant time.
+    let outside = (0..rows)
+        .map(|row| {
+            let mut counts = Vec::with_capacity(columns + 1);
+            counts.push(0);
+            for column in 0..columns {
+                let last = counts[column];
+                counts.push(last + usize::from(!grid.is_inside(column, row)));
+            }
+            counts
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let all_inside = |row: usize, first: usize, last: usize| {
+        outside[row][last + 1] == outside[row][first]
+    };
+
+    let mut rects = Vec::new();
+    for bottom in 0..rows {
+        let mut open = (0..columns)
+            .map(|column| grid.is_inside(column, bottom))
+            .collect::<Vec<bool>>();
+        for top in bottom..rows {
+            if top > bottom {
+                for (column, still_open) in open.iter_mut().enumerate() {
+                    *still_open &= grid.is_inside(column, top);
+                }
+            }
+            if !open.iter().any(|&o| o) {
+                break;
+            }
+
+            let mut column = 0;
+            while column < columns {
+                if !open[column] {
+                    column += 1;
+                    continue;
+                }
+                let first = column;
+                while column < columns && open[column] {
+                    column += 1;
+                }
+                let last = column - 1;
+                let grows_down =
+                    bottom > 0 && all_inside(bottom - 1, first, last);
+                let grows_up =
+                    top + 1 < rows && all_inside(top + 1, first, last);
+                if !grows_down && !grows_up {
+                    rects.push(Rect::from_bounds(
+                        grid.xs()[first],
+                        grid.ys()[bottom],
+                        grid.xs()[last + 1],
+                        grid.ys()[top + 1],
+                    ));
+                }
+            }
+        }
+    }
+    rects
+}
+
+impl Decomposer {
+    /// Cover a polygon with every maximal axis-aligned rect inside it.
+    ///
+    /// Unlike `decompose`, the rects overlap: each one is as large as it can
+    /// be without leaving the polygon. Only the rects accepted by `filter`
+    /// are returned.
+    pub fn cover(
+        points: Vec<Point>,
+        filter: CoverFilter,
+    ) -> Result<Vec<Rect>, DecompErr> {
+        let grid = CompressedGrid::from_geometry(&Geometry::new(points)?, &[]);
+        Ok(maximal_rects(&grid)
+            .into_iter()
+            .filter(|rect| filter.accepts(rect))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    fn sorted(mut rects: Vec<Rect>) -> Vec<Rect> {
+        rects.sort_unstable_by_key(|rect| {
+            (rect.x_min(), rect.y_min(), rect.x_max(), rect.y_max())
+        });
+        rects
+    }
+
+    #[test]
+    fn rect_covers_itself() {
+        let points = ring(&[(0, 0), (0, 2), (5, 2), (5, 0)]);
+        let cover = Decomposer::cover(points, CoverFilter::default()).unwrap();
+        assert_eq!(cover, vec![Rect::from_bounds(0, 0, 5, 2)]);
+    }
+
+    #[test]
+    fn u_shape_has_overlapping_rects() {
+        let points = ring(&[
+            (0, 0),
+            (0, 3),
+            (1, 3),
+            (1, 1),
+            (2, 1),
+            (2, 3),
+            (3, 3),
+            (3, 0),
+        ]);
+        let cover = Decomposer::cover(points, CoverFilter::default()).unwrap();
+        assert_eq!(
+            sorted(cover),
+            vec![
+                Rect::from_bounds(0, 0, 1, 3),
+                Rect::from_bounds(0, 0, 3, 1),
+                Rect::from_bounds(2, 0, 3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_drops_narrow_rects() {
+        let points = ring(&[(0, 0), (0, 1), (1, 1), (1, 4), (2, 4), (2, 0)]);
+        let all = Decomposer::cover(points.clone(), CoverFilter::default());
+        assert_eq!(all.unwrap().len(), 2);
+        let filter = CoverFilter {
+            min_width: 2,
+            min_height: 0,
+        };
+        assert!(filter.accepts(&Rect::from_bounds(0, 0, 2, 1)));
+        assert_eq!(
+            Decomposer::cover(points, filter).unwrap(),
+            vec![Rect::from_bounds(0, 0, 2, 1)]
+        );
+    }
+
+    #[test]
+    fn too_few_points() {
+        let points = ring(&[(0, 0), (0, 1)]);
+        assert_eq!(
+            Decomposer::cover(points, CoverFilter::default()).unwrap_err(),
+            DecompErr::NotEnoughPoints
+        );
+    }
+}
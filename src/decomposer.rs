@@ -5,7 +5,8 @@ use tracing::info;
 
 use crate::active::{ActiveEdges, ActiveNodes, ActiveVec};
 use crate::debug::COLOR_ORANGE;
-use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::provenance::{DecomposedRect, HorizontalBoundary};
 use crate::rect::Rect;
 use crate::{
     active::Cursor,
@@ -19,10 +20,22 @@ use crate::{
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DecompErr {
     NotEnoughPoints,
     FailedScanlineUpdate,
     IsAlreadySimple,
+    /// An edge is neither horizontal, vertical, nor at 45 degrees.
+    NotOctilinear,
+    /// More concave vertices than an exhaustive search accepts.
+    TooManyConcaveVertices,
+    /// A shot below the minimum shot size cannot be avoided.
+    ShotBelowMinimum,
+    /// A shot rule whose writing grid is not positive, or whose maximum shot
+    /// size is less than one step of it.
+    InvalidShotRule,
+    /// A bound on the size of the rects is zero or negative.
+    NonPositiveBound,
 }
 
 impl Display for DecompErr {
@@ -38,6 +51,9 @@ pub struct Decomposer {
     pub active_nodes: ActiveNodes,
     pub active_edges: ActiveEdges,
     pub scanline: isize,
+    /// Number of nodes the geometry started out with, before any splits.
+    pub original_nodes: usize,
+    pub boundary: HorizontalBoundary,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -53,7 +69,8 @@ pub enum ScanResult {
     ReturnRects,
     ContinueLoop(EdgeScans),
     ContinueSplit(EdgeScans),
-    NewRect(Rect),
+    /// A new rect, along with the left and right edges bounding it.
+    NewRect(Rect, Edge, Edge),
 }
 
 macro_rules! check_return {
@@ -258,6 +275,8 @@ impl EdgeScans {
                 self.le().source(geometry).point,
                 self.re().source(geometry).point,
             ),
+            *self.le(),
+            *self.re(),
         )
     }
 }
@@ -300,12 +319,20 @@ impl Decomposer {
             dbg_active_nodes!(geometry, &active_nodes)
         );
 
+        // No splits have happened yet, so every node is an original one.
+        let original_nodes = geometry.len_nodes();
+
         Ok(Self {
             active_nodes,
             active_edges,
             // Based on:
             // https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#205
             scanline: 0,
+            original_nodes,
+            boundary: HorizontalBoundary::from_geometry(
+                geometry,
+                original_nodes,
+            ),
         })
     }
 
@@ -365,8 +392,8 @@ impl Decomposer {
     fn scan_and_split(
         &mut self,
         geometry: &mut Geometry,
-        mut rects: Vec<Rect>,
-    ) -> Vec<Rect> {
+        mut rects: Vec<DecomposedRect>,
+    ) -> Vec<DecomposedRect> {
         // Based on:
         // https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#L258-L320
         // See also the comment by CTRL+F for PURGE_ACTIVE_EDGES
@@ -391,11 +418,17 @@ impl Decomposer {
                 ScanResult::ContinueLoop(s) => {
                     edge_scan = s;
                 }
-                ScanResult::NewRect(rect) => {
+                ScanResult::NewRect(rect, left, right) => {
                     emit_info!(
                         fmt:"pushing rect: {:?}" | rect
                     );
-                    rects.push(rect);
+                    rects.push(DecomposedRect::new(
+                        rect,
+                        &left,
+                        &right,
+                        self.original_nodes,
+                        &self.boundary,
+                    ));
                 }
                 ScanResult::ContinueSplit(_) => unreachable!(),
             }
@@ -448,8 +481,24 @@ impl Decomposer {
     ///
     /// Based on:
     /// https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#L183
-    pub fn decompose(points: Vec<Point>) -> Result<Vec<Rect>, DecompErr> {
-        let mut geometry = Geometry::new(points)?;
+    pub fn decompose(
+        polygon: impl Into<Polygon>,
+    ) -> Result<Vec<Rect>, DecompErr> {
+        Self::decompose_with_provenance(polygon).map(|rects| {
+            rects
+                .into_iter()
+                .map(|decomposed| decomposed.rect)
+                .collect()
+        })
+    }
+
+    /// Like `decompose`, but each rect also records the polygon edges its
+    /// left and right walls lie on, and whether its top and bottom are on the
+    /// polygon's boundary or on a cut made by the scanline.
+    pub fn decompose_with_provenance(
+        polygon: impl Into<Polygon>,
+    ) -> Result<Vec<DecomposedRect>, DecompErr> {
+        let mut geometry = Geometry::new(polygon.into().into_points())?;
         let mut decomposer = Self::new(&geometry)?;
 
         // TODO: figure out whether its worth pre-allocating rects. If yes, then
@@ -25,6 +25,10 @@ pub struct Edge {
     pub source: NodeId,
     pub target: NodeId,
     pub side: Side,
+    /// The polygon edge this edge lies on, as the index of its first vertex
+    /// in the points the geometry was built from. Both pieces of a split
+    /// edge keep the origin of the edge they were split from.
+    pub origin: usize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -34,12 +38,19 @@ pub enum Containment {
 }
 
 impl Edge {
-    pub fn new(id: EdgeId, source: NodeId, target: NodeId, ty: Side) -> Self {
+    pub fn new(
+        id: EdgeId,
+        source: NodeId,
+        target: NodeId,
+        ty: Side,
+        origin: usize,
+    ) -> Self {
         Self {
             id,
             source,
             target,
             side: ty,
+            origin,
         }
     }
 
@@ -0,0 +1,356 @@
+// Based on:
+// LEF/DEF 5.8 Language Reference, "Layer (Cut)": ENCLOSURE
+
+use crate::{
+    boolean::BooleanOp,
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+    rtree::RTree,
+};
+
+/// A side of a cut rect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CutSide {
+    Left,
+    Bottom,
+    Right,
+    Top,
+}
+
+impl CutSide {
+    pub const ALL: [CutSide; 4] =
+        [CutSide::Left, CutSide::Bottom, CutSide::Right, CutSide::Top];
+
+    /// `rect` pushed out by `by` on this side only.
+    fn extend(self, rect: &Rect, by: isize) -> Rect {
+        let (x0, y0, x1, y1) =
+            (rect.x_min(), rect.y_min(), rect.x_max(), rect.y_max());
+        match self {
+            CutSide::Left => Rect::from_bounds(x0 - by, y0, x1, y1),
+            CutSide::Bottom => Rect::from_bounds(x0, y0 - by, x1, y1),
+            CutSide::Right => Rect::from_bounds(x0, y0, x1 + by, y1),
+            CutSide::Top => Rect::from_bounds(x0, y0, x1, y1 + by),
+        }
+    }
+
+    /// How far `other`, lying outside `rect` on this side, is from it.
+    fn gap(self, rect: &Rect, other: &Rect) -> isize {
+        match self {
+            CutSide::Left => rect.x_min() - other.x_max(),
+            CutSide::Bottom => rect.y_min() - other.y_max(),
+            CutSide::Right => other.x_min() - rect.x_max(),
+            CutSide::Top => other.y_min() - rect.y_max(),
+        }
+    }
+}
+
+/// The metal layer enclosing a cut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Below,
+    Above,
+}
+
+/// How far the metal must extend beyond the sides of a cut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnclosureRule {
+    /// A separate enclosure for each side.
+    PerSide {
+        left: isize,
+        bottom: isize,
+        right: isize,
+        top: isize,
+    },
+    /// `overhang1` on two opposite sides and `overhang2` on the other two,
+    /// whichever pair of sides gets which, as in a LEF
+    /// `ENCLOSURE overhang1 overhang2`.
+    TwoSides { overhang1: isize, overhang2: isize },
+}
+
+impl EnclosureRule {
+    pub fn uniform(overhang: isize) -> Self {
+        EnclosureRule::TwoSides {
+            overhang1: overhang,
+            overhang2: overhang,
+        }
+    }
+
+    /// The enclosures required on each side of `CutSide::ALL`, for each of
+    /// the ways the rule can be met.
+    fn alternatives(&self) -> Vec<[isize; 4]> {
+        match *self {
+            EnclosureRule::PerSide {
+                left,
+                bottom,
+                right,
+                top,
+            } => vec![[left, bottom, right, top]],
+            EnclosureRule::TwoSides {
+                overhang1,
+                overhang2,
+            } => vec![
+                [overhang1, overhang2, overhang1, overhang2],
+                [overhang2, overhang1, overhang2, overhang1],
+            ],
+        }
+    }
+}
+
+/// The rules for both metal layers of a via.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViaEnclosure {
+    pub below: EnclosureRule,
+    pub above: EnclosureRule,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnclosureFault {
+    /// Part of the cut is not covered by the metal at all: `marker` bounds
+    /// the uncovered part.
+    Uncovered { marker: Rect },
+    /// The metal does not extend far enough beyond `side` of the cut.
+    Short {
+        side: CutSide,
+        enclosure: isize,
+        required: isize,
+    },
+}
+
+impl EnclosureFault {
+    /// By how much the enclosure falls short of the rule, zero for
+    /// uncovered cuts.
+    pub fn shortfall(&self) -> isize {
+        match self {
+            EnclosureFault::Uncovered { .. } => 0,
+            EnclosureFault::Short {
+                enclosure,
+                required,
+                ..
+            } => required - enclosure,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnclosureViolation {
+    /// Index of the cut in the checked batch.
+    pub cut: usize,
+    pub level: Level,
+    pub fault: EnclosureFault,
+}
+
+/// The enclosure of `cut` by `metal` on each side of `CutSide::ALL`, up to
+/// `reach`, or the bounds of the part of the cut `metal` leaves uncovered.
+///
+/// The enclosure on a side is found by growing the cut on that side alone,
+/// and measuring how far from the cut the first part of the grown rect
+/// `metal` does not cover lies.
+fn enclosures(
+    cut: &Rect,
+    metal: &[Rect],
+    reach: isize,
+) -> Result<[isize; 4], Rect> {
+    let uncovered = |rect: Rect| {
+        BooleanOp::Difference
+            .apply(&[rect], metal)
+            .into_iter()
+            .filter(|rect| !rect.is_degenerate())
+            .collect::<Vec<Rect>>()
+    };
+    if let Some(marker) = uncovered(*cut).into_iter().reduce(|a, b| a.union(&b))
+    {
+        return Err(marker);
+    }
+    Ok(CutSide::ALL.map(|side| {
+        uncovered(side.extend(cut, reach))
+            .iter()
+            .map(|rect| side.gap(cut, rect))
+            .min()
+            .unwrap_or(reach)
+    }))
+}
+
+/// The faults of the cut, for the way of meeting the rule it is the closest
+/// to, if it meets none.
+fn check_cut(
+    cut: &Rect,
+    metal: &RTree<usize>,
+    rule: &EnclosureRule,
+) -> Vec<EnclosureFault> {
+    let alternatives = rule.alternatives();
+    let reach = alternatives.iter().flatten().copied().max().unwrap_or(0);
+    let window = Rect::from_bounds(
+        cut.x_min() - reach,
+        cut.y_min() - reach,
+        cut.x_max() + reach,
+        cut.y_max() + reach,
+    );
+    let rects = metal
+        .window(&window)
+        .into_iter()
+        .map(|(rect, _)| *rect)
+        .collect::<Vec<Rect>>();
+    let enclosures = match enclosures(cut, &rects, reach) {
+        Ok(enclosures) => enclosures,
+        Err(marker) => return vec![EnclosureFault::Uncovered { marker }],
+    };
+    alternatives
+        .iter()
+        .map(|required| {
+            CutSide::ALL
+                .into_iter()
+                .zip(enclosures)
+                .zip(*required)
+                .filter(|&((_, enclosure), required)| enclosure < required)
+                .map(|((side, enclosure), required)| EnclosureFault::Short {
+                    side,
+                    enclosure,
+                    required,
+                })
+                .collect::<Vec<EnclosureFault>>()
+        })
+        .min_by_key(|faults| {
+            faults.iter().map(EnclosureFault::shortfall).sum::<isize>()
+        })
+        .unwrap_or_default()
+}
+
+impl Decomposer {
+    /// Check that every cut is enclosed by the metal polygons (given as
+    /// clockwise points) of the layers below and above it.
+    ///
+    /// The polygons of each layer are decomposed and indexed together, so a
+    /// cut may be enclosed by several touching or overlapping polygons. A
+    /// cut that is not entirely covered by a layer is reported once for that
+    /// layer; otherwise every side whose enclosure falls short is reported.
+    /// Under a `TwoSides` rule, the sides reported are those of the way of
+    /// meeting the rule that falls short by the least.
+    pub fn check_enclosure(
+        cuts: &[Rect],
+        below: &[Vec<Point>],
+        above: &[Vec<Point>],
+        rule: &ViaEnclosure,
+    ) -> Result<Vec<EnclosureViolation>, DecompErr> {
+        let below_metal = Decomposer::index(below.iter().cloned().enumerate())?;
+        let above_metal = Decomposer::index(above.iter().cloned().enumerate())?;
+        let mut violations = Vec::new();
+        for (ix, cut) in cuts.iter().enumerate() {
+            for (level, metal, rule) in [
+                (Level::Below, &below_metal, &rule.below),
+                (Level::Above, &above_metal, &rule.above),
+            ] {
+                violations.extend(check_cut(cut, metal, rule).into_iter().map(
+                    |fault| EnclosureViolation {
+                        cut: ix,
+                        level,
+                        fault,
+                    },
+                ));
+            }
+        }
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: isize, y0: isize, x1: isize, y1: isize) -> Vec<Point> {
+        [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+            .map(Point::from)
+            .to_vec()
+    }
+
+    /// Check the cut `(0, 0, 2, 2)` against the metal below, with no rule
+    /// for the metal above, which covers everything.
+    fn check_below(
+        below: &[Vec<Point>],
+        rule: EnclosureRule,
+    ) -> Vec<EnclosureViolation> {
+        let rule = ViaEnclosure {
+            below: rule,
+            above: EnclosureRule::uniform(0),
+        };
+        let cut = Rect::from_bounds(0, 0, 2, 2);
+        let above = [rect(-10, -10, 10, 10)];
+        Decomposer::check_enclosure(&[cut], below, &above, &rule).unwrap()
+    }
+
+    #[test]
+    fn uniform_enclosure() {
+        let metal = [rect(-1, -1, 3, 3)];
+        assert!(check_below(&metal, EnclosureRule::uniform(1)).is_empty());
+        let violations = check_below(&metal, EnclosureRule::uniform(2));
+        assert_eq!(
+            violations
+                .iter()
+                .map(|violation| violation.fault)
+                .collect::<Vec<EnclosureFault>>(),
+            CutSide::ALL.map(|side| EnclosureFault::Short {
+                side,
+                enclosure: 1,
+                required: 2,
+            })
+        );
+        assert!(violations.iter().all(|violation| violation.cut == 0
+            && violation.level == Level::Below
+            && violation.fault.shortfall() == 1));
+    }
+
+    #[test]
+    fn touching_polygons_enclose_together() {
+        let metal = [rect(-1, -1, 1, 3), rect(1, -1, 3, 3)];
+        assert!(check_below(&metal, EnclosureRule::uniform(1)).is_empty());
+    }
+
+    #[test]
+    fn two_sides_either_way() {
+        let rule = EnclosureRule::TwoSides {
+            overhang1: 0,
+            overhang2: 2,
+        };
+        assert!(check_below(&[rect(-2, 0, 4, 2)], rule).is_empty());
+        assert!(check_below(&[rect(0, -2, 2, 4)], rule).is_empty());
+        // Closer to extending left and right: only those sides fall short.
+        assert_eq!(
+            check_below(&[rect(-1, 0, 3, 2)], rule)
+                .iter()
+                .map(|violation| violation.fault)
+                .collect::<Vec<EnclosureFault>>(),
+            [CutSide::Left, CutSide::Right].map(|side| EnclosureFault::Short {
+                side,
+                enclosure: 1,
+                required: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn per_side_and_uncovered() {
+        let rule = EnclosureRule::PerSide {
+            left: 0,
+            bottom: 1,
+            right: 0,
+            top: 0,
+        };
+        assert!(check_below(&[rect(0, -1, 2, 2)], rule).is_empty());
+        assert_eq!(
+            check_below(&[rect(0, -1, 1, 2)], rule),
+            vec![EnclosureViolation {
+                cut: 0,
+                level: Level::Below,
+                fault: EnclosureFault::Uncovered {
+                    marker: Rect::from_bounds(1, 0, 2, 2),
+                },
+            }]
+        );
+        assert_eq!(
+            check_below(&[], rule)[0].fault,
+            EnclosureFault::Uncovered {
+                marker: Rect::from_bounds(0, 0, 2, 2),
+            }
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    polygon::Polygon,
+    rect::Rect,
+};
+
+/// The constraints of a mask writer on the rects (shots) it exposes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShotRule {
+    /// Largest width and height of a shot.
+    pub max_shot: isize,
+    /// Smallest width and height of a shot.
+    pub min_shot: isize,
+    /// Shots are cut on multiples of the writing grid.
+    pub grid: isize,
+}
+
+/// The shots a polygon is fractured into.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fracture {
+    pub shots: Vec<Rect>,
+}
+
+impl Fracture {
+    #[inline]
+    pub fn shot_count(&self) -> usize {
+        self.shots.len()
+    }
+}
+
+/// Cut `[lo, hi]` into as few pieces no longer than `max` as possible, as
+/// evenly as possible, with every cut on a multiple of `grid`. Returns the
+/// bounds of the pieces, from `lo` to `hi`.
+///
+/// `max` is rounded down to a multiple of `grid`, so pieces stay within it
+/// whatever the rounding of the cuts. If `lo` or `hi` are off the grid, the
+/// first or last piece ends on the nearest grid line. `grid` must be
+/// positive and `max` at least `grid`: otherwise they are raised to 1 and
+/// `grid`, and pieces may exceed `max`.
+pub fn balanced_cuts(
+    lo: isize,
+    hi: isize,
+    max: isize,
+    grid: isize,
+) -> Vec<isize> {
+    let grid = grid.max(1);
+    let max = (max / grid).max(1) * grid;
+    let length = (hi - lo).max(0);
+    let mut pieces = (length - 1).max(0) / max + 1;
+    loop {
+        let mut cuts = vec![lo];
+        cuts.extend((1..pieces).map(|k| {
+            // The grid line nearest to `lo + k * length / pieces`.
+            let num = lo as i128 * pieces as i128 + k as i128 * length as i128;
+            let den = pieces as i128 * grid as i128;
+            let line = (2 * num + den).div_euclid(2 * den);
+            (line as isize * grid).clamp(lo, hi)
+        }));
+        cuts.push(hi);
+        cuts.dedup();
+        if cuts.windows(2).all(|piece| piece[1] - piece[0] <= max) {
+            return cuts;
+        }
+        pieces += 1;
+    }
+}
+
+impl ShotRule {
+    /// Whether shots no larger than the maximum can be cut on the grid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.grid > 0 && self.max_shot >= self.grid
+    }
+
+    /// Cut `rect` into a grid of shots, balancing the widths of its columns
+    /// and the heights of its rows. The rule must be valid (see
+    /// `is_valid`).
+    pub fn shots(&self, rect: &Rect) -> Vec<Rect> {
+        let xs =
+            balanced_cuts(rect.x_min(), rect.x_max(), self.max_shot, self.grid);
+        let ys =
+            balanced_cuts(rect.y_min(), rect.y_max(), self.max_shot, self.grid);
+        ys.windows(2)
+            .flat_map(|row| {
+                xs.windows(2).map(move |column| {
+                    Rect::from_bounds(column[0], row[0], column[1], row[1])
+                })
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn is_undersized(&self, shot: &Rect) -> bool {
+        shot.width() < self.min_shot || shot.height() < self.min_shot
+    }
+}
+
+impl Decomposer {
+    /// Fracture a polygon into shots for a mask writer: decompose it, and cut
+    /// each rect larger than the maximum shot size into a grid of shots.
+    ///
+    /// The polygon is decomposed by `decompose_sliver_aware`, so that rects
+    /// the scanline would leave thinner than the minimum shot size are re-cut
+    /// along the other axis where that avoids them. Balancing the cuts keeps
+    /// them from making new slivers. If a shot is still narrower or shorter
+    /// than the minimum, as where the polygon itself is that narrow, the
+    /// polygon cannot be fractured and `ShotBelowMinimum` is returned.
+    ///
+    /// Returns `InvalidShotRule` if the rule is not valid (see
+    /// `ShotRule::is_valid`), as shots within the maximum could not be cut
+    /// on its grid.
+    pub fn fracture_shots(
+        polygon: impl Into<Polygon>,
+        rule: &ShotRule,
+    ) -> Result<Fracture, DecompErr> {
+        if !rule.is_valid() {
+            return Err(DecompErr::InvalidShotRule);
+        }
+        let decomposition = Decomposer::decompose_sliver_aware(
+            polygon.into().into_points(),
+            rule.min_shot,
+        )?;
+        let shots = decomposition
+            .rects
+            .iter()
+            .flat_map(|rect| rule.shots(rect))
+            .collect::<Vec<Rect>>();
+        if shots.iter().any(|shot| rule.is_undersized(shot)) {
+            return Err(DecompErr::ShotBelowMinimum);
+        }
+        Ok(Fracture { shots })
+    }
+
+    /// Fracture each polygon of a batch, e.g. a layer, with
+    /// `fracture_shots`.
+    pub fn fracture_layer(
+        polygons: &[Vec<Point>],
+        rule: &ShotRule,
+    ) -> Result<Vec<Fracture>, DecompErr> {
+        polygons
+            .iter()
+            .map(|points| Decomposer::fracture_shots(points.as_slice(), rule))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    fn lengths(cuts: &[isize]) -> Vec<isize> {
+        cuts.windows(2).map(|piece| piece[1] - piece[0]).collect()
+    }
+
+    #[test]
+    fn cuts_are_balanced_on_the_grid() {
+        assert_eq!(balanced_cuts(0, 10, 10, 1), [0, 10]);
+        assert_eq!(balanced_cuts(0, 11, 10, 1), [0, 6, 11]);
+        assert_eq!(lengths(&balanced_cuts(0, 100, 30, 1)), [25, 25, 25, 25]);
+        // 35 rounds down to 30 on a grid of 10.
+        assert_eq!(balanced_cuts(0, 70, 35, 10), [0, 20, 50, 70]);
+        assert_eq!(balanced_cuts(-3, 61, 30, 10), [-3, 20, 40, 61]);
+        assert_eq!(balanced_cuts(5, 5, 10, 1), [5]);
+    }
+
+    #[test]
+    fn rects_are_cut_into_shots() {
+        let rule = ShotRule {
+            max_shot: 10,
+            min_shot: 2,
+            grid: 1,
+        };
+        let shots = rule.shots(&Rect::from_bounds(0, 0, 25, 10));
+        assert_eq!(shots.len(), 3);
+        assert_eq!(shots.iter().map(Rect::area).sum::<i128>(), 250);
+        assert!(shots
+            .iter()
+            .all(|shot| shot.width() <= 10 && shot.height() <= 10));
+    }
+
+    #[test]
+    fn scanline_slivers_are_recut() {
+        let rule = ShotRule {
+            max_shot: 100,
+            min_shot: 5,
+            grid: 1,
+        };
+        // The scanline cuts a 1-high sliver off the top of the wide part.
+        let points =
+            ring(&[(0, 0), (0, 10), (10, 10), (10, 9), (20, 9), (20, 0)]);
+        let fracture = Decomposer::fracture_shots(points, &rule).unwrap();
+        assert_eq!(fracture.shot_count(), 2);
+        assert!(fracture
+            .shots
+            .iter()
+            .all(|shot| shot.width() >= 5 && shot.height() >= 5));
+        assert_eq!(fracture.shots.iter().map(Rect::area).sum::<i128>(), 190);
+    }
+
+    #[test]
+    fn narrow_polygons_cannot_be_fractured() {
+        let rule = ShotRule {
+            max_shot: 100,
+            min_shot: 5,
+            grid: 1,
+        };
+        let l = ring(&[(0, 0), (0, 20), (2, 20), (2, 5), (20, 5), (20, 0)]);
+        assert_eq!(
+            Decomposer::fracture_shots(l.clone(), &rule),
+            Err(DecompErr::ShotBelowMinimum)
+        );
+        let fractures = Decomposer::fracture_layer(
+            &[l],
+            &ShotRule {
+                min_shot: 2,
+                ..rule
+            },
+        )
+        .unwrap();
+        assert_eq!(fractures[0].shot_count(), 2);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let square = ring(&[(0, 0), (0, 10), (10, 10), (10, 0)]);
+        for (max_shot, grid) in [(4, 5), (0, 1), (-5, 1), (5, 0), (5, -1)] {
+            let rule = ShotRule {
+                max_shot,
+                min_shot: 0,
+                grid,
+            };
+            assert!(!rule.is_valid());
+            assert_eq!(
+                Decomposer::fracture_shots(square.clone(), &rule),
+                Err(DecompErr::InvalidShotRule)
+            );
+        }
+        let rule = ShotRule {
+            max_shot: 5,
+            min_shot: 0,
+            grid: 5,
+        };
+        assert!(rule.is_valid());
+        assert_eq!(
+            Decomposer::fracture_shots(square, &rule)
+                .unwrap()
+                .shot_count(),
+            4
+        );
+    }
+
+    #[test]
+    fn unbalanceable_lengths_are_rejected() {
+        // 11 cannot be cut into pieces of 8 to 10.
+        let rule = ShotRule {
+            max_shot: 10,
+            min_shot: 8,
+            grid: 1,
+        };
+        let square = ring(&[(0, 0), (0, 9), (11, 9), (11, 0)]);
+        assert_eq!(
+            Decomposer::fracture_shots(square, &rule),
+            Err(DecompErr::ShotBelowMinimum)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+// Based on:
+// https://datatracker.ietf.org/doc/html/rfc7946
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    outline::Outline,
+    point::Point,
+    provenance::DecomposedRect,
+    rect::Rect,
+    shape::{float_coordinate, gis_rings, rect_outline, ShapeErr},
+};
+
+fn coordinate(value: &Value) -> Result<isize, ShapeErr> {
+    if let Some(x) = value.as_i64() {
+        isize::try_from(x).map_err(|_| ShapeErr::OutOfRange(value.to_string()))
+    } else if let Some(x) = value.as_f64() {
+        float_coordinate(x, &value.to_string())
+    } else {
+        Err(ShapeErr::NotIntegral(value.to_string()))
+    }
+}
+
+fn array<'a>(value: &'a Value, what: &str) -> Result<&'a [Value], ShapeErr> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| ShapeErr::Syntax(format!("expected {}", what)))
+}
+
+/// A position is `[x, y]`, optionally followed by an altitude, which is
+/// ignored.
+fn position(value: &Value) -> Result<Point, ShapeErr> {
+    match array(value, "a position")? {
+        [x, y, ..] => Ok(Point::new(coordinate(x)?, coordinate(y)?)),
+        _ => Err(ShapeErr::Syntax("expected two coordinates".to_string())),
+    }
+}
+
+fn polygon(coordinates: &Value) -> Result<Outline, ShapeErr> {
+    let mut rings = array(coordinates, "a list of rings")?
+        .iter()
+        .map(|ring| {
+            array(ring, "a ring")?
+                .iter()
+                .map(position)
+                .collect::<Result<Vec<Point>, _>>()
+        })
+        .collect::<Result<Vec<Vec<Point>>, _>>()?;
+    if rings.is_empty() {
+        return Err(ShapeErr::NotEnoughPoints);
+    }
+    let outer = rings.remove(0);
+    Outline::from_rings(outer, rings)
+}
+
+/// Read the polygons of a GeoJSON object: a `Polygon` or `MultiPolygon`
+/// geometry, a `GeometryCollection` of them, or a `Feature` or
+/// `FeatureCollection` carrying them. Each ring is normalized by
+/// `normalize_ring`.
+pub fn read(value: &Value) -> Result<Vec<Outline>, ShapeErr> {
+    let members = |key: &str| -> Result<Vec<Outline>, ShapeErr> {
+        let mut outlines = Vec::new();
+        for member in array(&value[key], key)? {
+            outlines.extend(read(member)?);
+        }
+        Ok(outlines)
+    };
+    match value["type"].as_str() {
+        Some("FeatureCollection") => members("features"),
+        Some("GeometryCollection") => members("geometries"),
+        Some("Feature") => match &value["geometry"] {
+            Value::Null => Ok(Vec::new()),
+            geometry => read(geometry),
+        },
+        Some("Polygon") => Ok(vec![polygon(&value["coordinates"])?]),
+        Some("MultiPolygon") => array(&value["coordinates"], "polygons")?
+            .iter()
+            .map(polygon)
+            .collect(),
+        Some(other) => Err(ShapeErr::Unsupported(other.to_string())),
+        None => Err(ShapeErr::Syntax("expected a \"type\"".to_string())),
+    }
+}
+
+/// Parse a GeoJSON document and `read` its polygons.
+pub fn read_str(text: &str) -> Result<Vec<Outline>, ShapeErr> {
+    let value = serde_json::from_str::<Value>(text)
+        .map_err(|err| ShapeErr::Syntax(err.to_string()))?;
+    read(&value)
+}
+
+fn rings(outline: &Outline) -> Value {
+    gis_rings(outline)
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .map(|p| json!([p.x, p.y]))
+                .collect::<Vec<Value>>()
+        })
+        .collect::<Vec<Vec<Value>>>()
+        .into()
+}
+
+/// A `Polygon` geometry, with its outer ring counter-clockwise and its holes
+/// clockwise.
+pub fn polygon_geometry(outline: &Outline) -> Value {
+    json!({ "type": "Polygon", "coordinates": rings(outline) })
+}
+
+/// A `MultiPolygon` geometry of several outlines.
+pub fn multipolygon_geometry(outlines: &[Outline]) -> Value {
+    let coordinates = outlines.iter().map(rings).collect::<Vec<Value>>();
+    json!({ "type": "MultiPolygon", "coordinates": coordinates })
+}
+
+/// A `MultiPolygon` geometry with one polygon per rect.
+pub fn rects_geometry(rects: &[Rect]) -> Value {
+    multipolygon_geometry(&rects.iter().map(rect_outline).collect::<Vec<_>>())
+}
+
+/// A `Feature` with the given geometry and properties.
+pub fn feature(geometry: Value, properties: Map<String, Value>) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+/// The properties exported for each rect of a decomposition.
+fn rect_properties(index: usize, rect: &DecomposedRect) -> Map<String, Value> {
+    [
+        ("index", json!(index)),
+        ("width", json!(rect.rect.width())),
+        ("height", json!(rect.rect.height())),
+        ("area", json!(rect.rect.area() as f64)),
+        ("top", json!(format!("{:?}", rect.top))),
+        ("bottom", json!(format!("{:?}", rect.bottom))),
+        ("left_is_split", json!(rect.left_is_split())),
+        ("right_is_split", json!(rect.right_is_split())),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect()
+}
+
+/// Export a decomposition (see `Decomposer::decompose_with_provenance`) as a
+/// `FeatureCollection` of one feature per rect, whose properties give its
+/// index, size, and what its sides lie on. The number of rects is given in
+/// the properties of the collection itself.
+pub fn decomposition(rects: &[DecomposedRect]) -> Value {
+    let features = rects
+        .iter()
+        .enumerate()
+        .map(|(index, rect)| {
+            feature(
+                polygon_geometry(&rect_outline(&rect.rect)),
+                rect_properties(index, rect),
+            )
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "type": "FeatureCollection",
+        "properties": { "rects": rects.len() },
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decomposer::Decomposer;
+
+    #[test]
+    fn polygon_round_trips() {
+        let outlines = read_str(
+            r#"{"type": "Polygon", "coordinates": [
+                [[0, 0], [3, 0], [3, 3], [0, 3], [0, 0]],
+                [[1, 1], [1, 2], [2, 2], [2, 1], [1, 1]]
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].area(), Some(8));
+        let geometry = polygon_geometry(&outlines[0]);
+        assert_eq!(
+            geometry["coordinates"][0],
+            json!([[3, 0], [3, 3], [0, 3], [0, 0], [3, 0]])
+        );
+        assert_eq!(read(&geometry).unwrap(), outlines);
+    }
+
+    #[test]
+    fn collections() {
+        let square = json!({
+            "type": "Polygon",
+            "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1], [0, 0]]],
+        });
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [
+                feature(square.clone(), Map::new()),
+                { "type": "Feature", "geometry": null },
+                feature(
+                    json!({ "type": "GeometryCollection", "geometries": [square] }),
+                    Map::new(),
+                ),
+                feature(
+                    rects_geometry(&[Rect::from_bounds(5, 5, 7, 6)]),
+                    Map::new(),
+                ),
+            ],
+        });
+        let outlines = read(&collection).unwrap();
+        assert_eq!(
+            outlines
+                .iter()
+                .map(Outline::area)
+                .collect::<Option<Vec<i128>>>(),
+            Some(vec![1, 1, 2])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            read(&json!({ "type": "LineString", "coordinates": [] })),
+            Err(ShapeErr::Unsupported("LineString".to_string()))
+        );
+        assert!(matches!(read(&json!({})), Err(ShapeErr::Syntax(_))));
+        assert!(matches!(read_str("{"), Err(ShapeErr::Syntax(_))));
+        assert_eq!(
+            read(&json!({ "type": "Polygon", "coordinates": [] })),
+            Err(ShapeErr::NotEnoughPoints)
+        );
+        assert_eq!(
+            read(&json!({
+                "type": "Polygon",
+                "coordinates": [[[0, 0], [0.5, 0], [0.5, 1], [0, 1]]],
+            })),
+            Err(ShapeErr::NotIntegral("0.5".to_string()))
+        );
+        assert_eq!(
+            read_str(
+                r#"{"type": "Polygon", "coordinates":
+                    [[[0, 0], [1e30, 0], [1e30, 1], [0, 1]]]}"#
+            ),
+            Err(ShapeErr::OutOfRange("1e+30".to_string()))
+        );
+        assert_eq!(
+            read_str(
+                r#"{"type": "Polygon", "coordinates":
+                    [[[0, 0], [18446744073709551615, 0], [0, 1]]]}"#
+            ),
+            Err(ShapeErr::OutOfRange("18446744073709551615".to_string()))
+        );
+    }
+
+    #[test]
+    fn decomposition_features() {
+        let l = [(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]
+            .map(Point::from)
+            .to_vec();
+        let rects = Decomposer::decompose_with_provenance(l).unwrap();
+        let collection = decomposition(&rects);
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), rects.len());
+        assert_eq!(collection["properties"]["rects"], json!(rects.len()));
+        assert_eq!(features[1]["properties"]["index"], json!(1));
+        assert_eq!(
+            features[1]["properties"]["width"],
+            json!(rects[1].rect.width())
+        );
+        assert_eq!(
+            read(&collection).unwrap(),
+            rects
+                .iter()
+                .map(|rect| rect_outline(&rect.rect))
+                .collect::<Vec<Outline>>()
+        );
+    }
+}
@@ -52,6 +52,7 @@ where
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Side {
     Left,
     Right,
@@ -83,10 +84,11 @@ impl Geometry {
         source: NodeId,
         target: NodeId,
         ty: Side,
+        origin: usize,
     ) -> Edge {
         let new_edge_id = self
             .edges
-            .alloc_with_id(|id| Edge::new(id, source, target, ty));
+            .alloc_with_id(|id| Edge::new(id, source, target, ty, origin));
         self[source].set_out_edge(new_edge_id);
         self[target].set_inc_edge(new_edge_id);
         self[new_edge_id]
@@ -135,7 +137,7 @@ impl Geometry {
                 break;
             }
             if let Some(side) = source_node.which_side(&self[target]) {
-                self.new_edge(source, target, side);
+                self.new_edge(source, target, side, s);
                 info!(
                     "new edge: {:?}",
                     dbg_edge!(self, self.edges.iter().last().unwrap().1)
@@ -158,6 +160,20 @@ impl Geometry {
         self.nodes.iter()
     }
 
+    /// The vertices of the polygon, in the order it was built with.
+    ///
+    /// Nodes added by `split_edge` are left out: they lie in the middle of a
+    /// vertical boundary edge, and are the only nodes with both an incoming
+    /// and an outgoing vertical edge.
+    pub fn boundary(&self) -> Vec<Point> {
+        self.iter_nodes()
+            .filter(|(_, node)| {
+                node.inc_edge().is_some() != node.out_edge().is_some()
+            })
+            .map(|(_, node)| node.point)
+            .collect()
+    }
+
     /// Initialized with the vertical edges needed for scanline intersection
     /// test.
     ///
@@ -202,6 +218,7 @@ impl Geometry {
         // and  https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#L309
         // existing corresponds to u (left)/w (right) in the original code
         let side = self[split_target].side;
+        let origin = self[split_target].origin;
         let input_node = match side {
             Side::Left => self[split_target].source,
             Side::Right => self[split_target].target,
@@ -237,7 +254,7 @@ impl Geometry {
                 // into the active edge list, if it still needs to be split.)
                 // Based on:
                 // https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#L304
-                self.new_edge(input_node, new_node_id, side)
+                self.new_edge(input_node, new_node_id, side, origin)
             }
             Side::Right => {
                 // input edge gets its target replaced by new node
@@ -253,7 +270,7 @@ impl Geometry {
                 // into the active edge list, if it still needs to be split.)
                 // Based on:
                 // https://github.com/bzm3r/OpenROAD/blob/ecc03c290346823a66fec78669dacc8a85aabb05/src/odb/src/zutil/poly_decomp.cpp#L314
-                self.new_edge(new_node_id, input_node, side)
+                self.new_edge(new_node_id, input_node, side, origin)
             }
         }
     }
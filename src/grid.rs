@@ -0,0 +1,229 @@
+use crate::{
+    boolean::rects_from_bands, geometry::Geometry, outline::push_interval,
+    point::Point, rect::Rect,
+};
+
+/// A polygon laid over the grid formed by the distinct x and y coordinates of
+/// its points (and any extra coordinates asked for), with each cell marked as
+/// inside or outside the polygon.
+///
+/// Column `c` spans `xs[c]..xs[c + 1]` and row `r` spans `ys[r]..ys[r + 1]`,
+/// so a grid of `n` distinct xs has `n - 1` columns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompressedGrid {
+    xs: Vec<isize>,
+    ys: Vec<isize>,
+    inside: Vec<bool>,
+}
+
+impl CompressedGrid {
+    /// Build the grid of a single ring.
+    pub fn new(
+        ring: &[Point],
+        extra_xs: impl IntoIterator<Item = isize>,
+        extra_ys: impl IntoIterator<Item = isize>,
+    ) -> Self {
+        Self::with_holes(ring, &[], extra_xs, extra_ys)
+    }
+
+    /// Build the grid of a ring with holes. A cell is inside if it is inside
+    /// `outer` and outside every hole; the winding of each ring does not
+    /// matter.
+    pub fn with_holes(
+        outer: &[Point],
+        holes: &[Vec<Point>],
+        extra_xs: impl IntoIterator<Item = isize>,
+        extra_ys: impl IntoIterator<Item = isize>,
+    ) -> Self {
+        let rings = std::iter::once(outer)
+            .chain(holes.iter().map(Vec::as_slice))
+            .collect::<Vec<&[Point]>>();
+        let coordinates = |extra: Vec<isize>, axis: fn(&Point) -> isize| {
+            let mut values = rings
+                .iter()
+                .flat_map(|ring| ring.iter().map(axis))
+                .chain(extra)
+                .collect::<Vec<_>>();
+            values.sort_unstable();
+            values.dedup();
+            values
+        };
+        let xs = coordinates(extra_xs.into_iter().collect(), |p| p.x);
+        let ys = coordinates(extra_ys.into_iter().collect(), |p| p.y);
+
+        let (columns, rows) =
+            (xs.len().saturating_sub(1), ys.len().saturating_sub(1));
+        let mut inside = vec![false; columns * rows];
+        for row in 0..rows {
+            // Coordinates are doubled, so that cell centers are integers.
+            let center = ys[row] + ys[row + 1];
+            let mut crossings = rings
+                .iter()
+                .flat_map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)))
+                .filter(|(p, q)| {
+                    p.x == q.x
+                        && 2 * p.y.min(q.y) < center
+                        && center < 2 * p.y.max(q.y)
+                })
+                .map(|(p, _)| 2 * p.x)
+                .collect::<Vec<isize>>();
+            crossings.sort_unstable();
+
+            let mut crossed = 0;
+            for column in 0..columns {
+                let center = xs[column] + xs[column + 1];
+                while crossed < crossings.len() && crossings[crossed] < center {
+                    crossed += 1;
+                }
+                inside[row * columns + column] = crossed % 2 == 1;
+            }
+        }
+
+        Self { xs, ys, inside }
+    }
+
+    /// Build the grid of a polygon's geometry, minus the geometries of its
+    /// holes. The rings are read back with `Geometry::boundary`, so this
+    /// holds even once the scanline has split their edges.
+    pub fn from_geometry(outer: &Geometry, holes: &[Geometry]) -> Self {
+        Self::with_holes(
+            &outer.boundary(),
+            &holes.iter().map(Geometry::boundary).collect::<Vec<_>>(),
+            [],
+            [],
+        )
+    }
+
+    /// The x coordinates of the vertical grid lines, in increasing order.
+    #[inline]
+    pub fn xs(&self) -> &[isize] {
+        &self.xs
+    }
+
+    /// The y coordinates of the horizontal grid lines, in increasing order.
+    #[inline]
+    pub fn ys(&self) -> &[isize] {
+        &self.ys
+    }
+
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.xs.len().saturating_sub(1)
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.ys.len().saturating_sub(1)
+    }
+
+    #[inline]
+    pub fn is_inside(&self, column: usize, row: usize) -> bool {
+        self.inside[row * self.columns() + column]
+    }
+
+    /// Index of the grid line at `x`, if there is one.
+    #[inline]
+    pub fn column_of(&self, x: isize) -> Option<usize> {
+        self.xs.binary_search(&x).ok()
+    }
+
+    /// Index of the grid line at `y`, if there is one.
+    #[inline]
+    pub fn row_of(&self, y: isize) -> Option<usize> {
+        self.ys.binary_search(&y).ok()
+    }
+
+    /// The cell at `(column, row)`, in original coordinates.
+    #[inline]
+    pub fn cell_rect(&self, column: usize, row: usize) -> Rect {
+        Rect::from_bounds(
+            self.xs[column],
+            self.ys[row],
+            self.xs[column + 1],
+            self.ys[row + 1],
+        )
+    }
+
+    /// The inside cells, merged into non-overlapping rects in original
+    /// coordinates: runs of cells within a row are merged first, then
+    /// identical runs of consecutive rows.
+    pub fn to_rects(&self) -> Vec<Rect> {
+        let bands = (0..self.rows())
+            .map(|row| {
+                let mut intervals = Vec::new();
+                for column in 0..self.columns() {
+                    if self.is_inside(column, row) {
+                        push_interval(
+                            &mut intervals,
+                            (self.xs[column], self.xs[column + 1]),
+                        );
+                    }
+                }
+                intervals
+            })
+            .collect::<Vec<_>>();
+        rects_from_bands(&bands, &self.ys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn l_shape() {
+        let grid = CompressedGrid::new(
+            &ring(&[(0, 0), (0, 10), (5, 10), (5, 4), (20, 4), (20, 0)]),
+            [],
+            [],
+        );
+        assert_eq!(grid.xs(), [0, 5, 20]);
+        assert_eq!(grid.ys(), [0, 4, 10]);
+        assert_eq!((grid.columns(), grid.rows()), (2, 2));
+        assert!(grid.is_inside(0, 0) && grid.is_inside(1, 0));
+        assert!(grid.is_inside(0, 1) && !grid.is_inside(1, 1));
+        assert_eq!(grid.column_of(5), Some(1));
+        assert_eq!(grid.row_of(5), None);
+        assert_eq!(grid.cell_rect(1, 0), Rect::from_bounds(5, 0, 20, 4));
+        assert_eq!(
+            grid.to_rects(),
+            vec![
+                Rect::from_bounds(0, 0, 20, 4),
+                Rect::from_bounds(0, 4, 5, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn holes_and_extra_lines() {
+        let outer = ring(&[(0, 0), (0, 3), (3, 3), (3, 0)]);
+        // Counter-clockwise: the winding of holes does not matter.
+        let hole = ring(&[(1, 1), (2, 1), (2, 2), (1, 2)]);
+        let grid = CompressedGrid::with_holes(&outer, &[hole], [10], [-1]);
+        assert_eq!(grid.xs(), [0, 1, 2, 3, 10]);
+        assert_eq!(grid.ys(), [-1, 0, 1, 2, 3]);
+        assert!(!grid.is_inside(1, 2));
+        assert!(!grid.is_inside(3, 1) && !grid.is_inside(0, 0));
+        assert_eq!(grid.to_rects().iter().map(Rect::area).sum::<i128>(), 8);
+    }
+
+    #[test]
+    fn geometry_after_split() {
+        let l = ring(&[(0, 0), (0, 10), (5, 10), (5, 4), (20, 4), (20, 0)]);
+        let mut geometry = Geometry::new(l.clone()).unwrap();
+        let left = geometry
+            .iter_edges()
+            .find(|(_, edge)| edge.src_x(&geometry) == 0)
+            .map(|(id, _)| id)
+            .unwrap();
+        geometry.split_edge(left, 4);
+        assert_eq!(
+            CompressedGrid::from_geometry(&geometry, &[]),
+            CompressedGrid::new(&l, [], [])
+        );
+    }
+}
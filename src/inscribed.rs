@@ -0,0 +1,129 @@
+use crate::{
+    cover::maximal_rects,
+    decomposer::{DecompErr, Decomposer},
+    geometry::Geometry,
+    grid::CompressedGrid,
+    point::Point,
+    rect::Rect,
+};
+
+/// What makes one inscribed rect larger than another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Objective {
+    #[default]
+    Area,
+    /// Largest width, with ties broken by area.
+    Width,
+    /// Largest height, with ties broken by area.
+    Height,
+}
+
+/// A query for the largest axis-aligned rect inside a polygon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InscribedQuery {
+    pub objective: Objective,
+    /// Only consider rects containing this point (boundary included).
+    pub contains: Option<Point>,
+    /// Only consider rects at least this wide.
+    pub min_width: isize,
+}
+
+impl InscribedQuery {
+    #[inline]
+    fn accepts(&self, rect: &Rect) -> bool {
+        rect.width() >= self.min_width
+            && self.contains.is_none_or(|p| rect.contains(&p))
+    }
+
+    #[inline]
+    fn key(&self, rect: &Rect) -> (i128, i128) {
+        match self.objective {
+            Objective::Area => (rect.area(), 0),
+            Objective::Width => (rect.width() as i128, rect.area()),
+            Objective::Height => (rect.height() as i128, rect.area()),
+        }
+    }
+}
+
+impl Decomposer {
+    /// Find the largest axis-aligned rect inside a polygon, as measured by
+    /// `query.objective` and subject to its constraints, or `None` if no rect
+    /// satisfies them.
+    ///
+    /// Any rect inside the polygon can be grown until it is maximal without
+    /// shrinking, losing a contained point or getting narrower, so only the
+    /// maximal rects of the polygon (see `Decomposer::cover`) are compared.
+    pub fn largest_inscribed(
+        points: Vec<Point>,
+        query: &InscribedQuery,
+    ) -> Result<Option<Rect>, DecompErr> {
+        let grid = CompressedGrid::from_geometry(&Geometry::new(points)?, &[]);
+        Ok(maximal_rects(&grid)
+            .into_iter()
+            .filter(|rect| query.accepts(rect))
+            .max_by_key(|rect| query.key(rect)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An L with a 4-high arm and a 5-wide foot.
+    fn l_shape() -> Vec<Point> {
+        [(0, 0), (0, 4), (1, 4), (1, 1), (5, 1), (5, 0)]
+            .iter()
+            .map(|&p| p.into())
+            .collect()
+    }
+
+    fn largest(query: InscribedQuery) -> Option<Rect> {
+        Decomposer::largest_inscribed(l_shape(), &query).unwrap()
+    }
+
+    #[test]
+    fn objectives() {
+        let foot = Rect::from_bounds(0, 0, 5, 1);
+        let arm = Rect::from_bounds(0, 0, 1, 4);
+        assert_eq!(largest(InscribedQuery::default()), Some(foot));
+        assert_eq!(
+            largest(InscribedQuery {
+                objective: Objective::Width,
+                ..Default::default()
+            }),
+            Some(foot)
+        );
+        assert_eq!(
+            largest(InscribedQuery {
+                objective: Objective::Height,
+                ..Default::default()
+            }),
+            Some(arm)
+        );
+    }
+
+    #[test]
+    fn constraints() {
+        assert_eq!(
+            largest(InscribedQuery {
+                contains: Some(Point::new(0, 3)),
+                ..Default::default()
+            }),
+            Some(Rect::from_bounds(0, 0, 1, 4))
+        );
+        assert_eq!(
+            largest(InscribedQuery {
+                min_width: 6,
+                ..Default::default()
+            }),
+            None
+        );
+        assert_eq!(
+            largest(InscribedQuery {
+                contains: Some(Point::new(3, 3)),
+                ..Default::default()
+            }),
+            None
+        );
+    }
+}
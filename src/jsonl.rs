@@ -0,0 +1,172 @@
+use std::io::{BufRead, Write};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+};
+
+/// One polygon of a batch: `{"id": "a", "points": [[0, 0], [0, 1], ...]}`.
+/// The `id` is optional, and is carried over to the decomposition.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolygonRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub points: Vec<Point>,
+}
+
+/// The decomposition of one polygon of a batch: either
+/// `{"id": "a", "rects": [[0, 0, 2, 1], ...]}` or
+/// `{"id": "a", "error": "NotEnoughPoints"}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecompositionRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rects: Vec<Rect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<DecompErr>,
+}
+
+impl PolygonRecord {
+    pub fn decompose(self) -> DecompositionRecord {
+        DecompositionRecord::new(self.id, Decomposer::decompose(self.points))
+    }
+}
+
+impl DecompositionRecord {
+    pub fn new(
+        id: Option<String>,
+        result: Result<Vec<Rect>, DecompErr>,
+    ) -> Self {
+        match result {
+            Ok(rects) => Self {
+                id,
+                rects,
+                error: None,
+            },
+            Err(error) => Self {
+                id,
+                rects: Vec::new(),
+                error: Some(error),
+            },
+        }
+    }
+
+    pub fn into_result(self) -> Result<Vec<Rect>, DecompErr> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.rects),
+        }
+    }
+}
+
+/// Read one JSON value per line, skipping blank lines. Each value comes with
+/// the number of its line, counting from 1.
+pub fn read_lines<T: DeserializeOwned>(
+    reader: impl BufRead,
+) -> impl Iterator<Item = (usize, serde_json::Result<T>)> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(ix, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some((ix + 1, serde_json::from_str(&line))),
+            Err(err) => Some((ix + 1, Err(serde_json::Error::io(err)))),
+        })
+}
+
+/// Write `value` as a single line of JSON.
+pub fn write_line<T: Serialize>(
+    mut writer: impl Write,
+    value: &T,
+) -> serde_json::Result<()> {
+    serde_json::to_writer(&mut writer, value)?;
+    writer.write_all(b"\n").map_err(serde_json::Error::io)
+}
+
+/// A line of a batch that could not be read as a record.
+#[derive(Debug)]
+pub struct MalformedLine {
+    pub line: usize,
+    pub error: serde_json::Error,
+}
+
+/// Decompose a batch of `PolygonRecord`s, one per line of `reader`, and
+/// write a `DecompositionRecord` per polygon to `writer`.
+///
+/// A malformed line is skipped, and returned once the rest of the batch is
+/// done, so that one bad record does not lose the others. Failing to read or
+/// write stops the batch.
+pub fn decompose_lines(
+    reader: impl BufRead,
+    mut writer: impl Write,
+) -> serde_json::Result<Vec<MalformedLine>> {
+    let mut malformed = Vec::new();
+    for (line, polygon) in read_lines::<PolygonRecord>(reader) {
+        match polygon {
+            Ok(polygon) => write_line(&mut writer, &polygon.decompose())?,
+            Err(error) if error.is_io() => return Err(error),
+            Err(error) => malformed.push(MalformedLine { line, error }),
+        }
+    }
+    Ok(malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let record: PolygonRecord = serde_json::from_str(
+            r#"{"id": "l", "points": [[0, 0], [0, 1], [1, 1], [1, 2], [2, 2], [2, 0]]}"#,
+        )
+        .unwrap();
+        assert_eq!(record.id.as_deref(), Some("l"));
+        let decomposition = record.decompose();
+        let json = serde_json::to_string(&decomposition).unwrap();
+        assert_eq!(json, r#"{"id":"l","rects":[[0,0,2,1],[1,1,2,2]]}"#);
+        assert_eq!(
+            serde_json::from_str::<DecompositionRecord>(&json).unwrap(),
+            decomposition
+        );
+
+        let error = PolygonRecord {
+            id: None,
+            points: vec![Point::new(0, 0)],
+        }
+        .decompose();
+        assert_eq!(
+            serde_json::to_string(&error).unwrap(),
+            r#"{"error":"NotEnoughPoints"}"#
+        );
+        assert_eq!(error.into_result(), Err(DecompErr::NotEnoughPoints));
+    }
+
+    #[test]
+    fn malformed_lines_do_not_stop_the_batch() {
+        let input = concat!(
+            r#"{"id": "a", "points": [[0, 0], [0, 1], [1, 1], [1, 0]]}"#,
+            "\n\nnot json\n",
+            r#"{"id": "b", "points": [[0, 0]]}"#,
+            "\n",
+            r#"{"id": "c"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        let malformed = decompose_lines(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            malformed.iter().map(|m| m.line).collect::<Vec<usize>>(),
+            [3, 5]
+        );
+        let records = read_lines::<DecompositionRecord>(output.as_slice())
+            .map(|(_, record)| record.unwrap())
+            .collect::<Vec<DecompositionRecord>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id.as_deref(), Some("a"));
+        assert_eq!(records[1].error, Some(DecompErr::NotEnoughPoints));
+    }
+}
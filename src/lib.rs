@@ -1,8 +1,37 @@
 pub mod active;
+pub mod adjacency;
+pub mod area;
+pub mod boolean;
+pub mod bounded;
+pub mod cover;
 mod debug;
 pub mod decomposer;
 pub mod edge;
+pub mod enclosure;
+pub mod fracture;
+#[cfg(feature = "serde")]
+pub mod geojson;
 pub mod geometry;
+pub mod grid;
+pub mod inscribed;
+#[cfg(feature = "serde")]
+pub mod jsonl;
+pub mod locate;
+pub mod lyrdb;
+pub mod min_cut;
 pub mod node;
+pub mod notch;
+pub mod oasis;
+pub mod octilinear;
+pub mod outline;
 pub mod point;
+pub mod polygon;
+pub mod provenance;
 pub mod rect;
+pub mod rtree;
+pub mod shape;
+pub mod sliver;
+pub mod spacing;
+pub mod transform;
+pub mod width;
+pub mod wkt;
@@ -0,0 +1,181 @@
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+};
+
+/// Where a point lies relative to a decomposed polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// Strictly inside the polygon, in the rect with this index. Points on a
+    /// cut between two rects are inside, and reported in one of them.
+    Inside(usize),
+    /// On the polygon's boundary, and on the boundary of the rect with this
+    /// index.
+    Boundary(usize),
+    Outside,
+}
+
+/// Answers point location queries against a set of non-overlapping rects,
+/// such as the decomposition of a polygon, in logarithmic time.
+///
+/// The distinct y bounds of the rects split the plane into horizontal slabs,
+/// and each slab keeps the x intervals of the rects spanning it, sorted. A
+/// point is located by looking at the four quadrants around it: it is inside
+/// if all of them are covered, outside if none are, and on the boundary
+/// otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct PointLocator {
+    rects: Vec<Rect>,
+    ys: Vec<isize>,
+    /// For each slab between consecutive `ys`, `(x_min, x_max, rect)`
+    /// sorted by `x_min`.
+    slabs: Vec<Vec<(isize, isize, usize)>>,
+}
+
+impl PointLocator {
+    /// Build the slabs of `rects`, which must not overlap. Degenerate rects
+    /// are ignored.
+    pub fn new(rects: Vec<Rect>) -> Self {
+        let mut ys = rects
+            .iter()
+            .filter(|rect| !rect.is_degenerate())
+            .flat_map(|rect| [rect.y_min(), rect.y_max()])
+            .collect::<Vec<isize>>();
+        ys.sort_unstable();
+        ys.dedup();
+
+        let mut slabs = vec![Vec::new(); ys.len().saturating_sub(1)];
+        for (ix, rect) in rects.iter().enumerate() {
+            if rect.is_degenerate() {
+                continue;
+            }
+            let first = ys.partition_point(|&y| y < rect.y_min());
+            let last = ys.partition_point(|&y| y < rect.y_max());
+            for slab in &mut slabs[first..last] {
+                slab.push((rect.x_min(), rect.x_max(), ix));
+            }
+        }
+        for slab in &mut slabs {
+            slab.sort_unstable();
+        }
+        Self { rects, ys, slabs }
+    }
+
+    #[inline]
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// The rect covering the points just right of (or left of, if not
+    /// `right`) and just above (or below, if not `up`) `p`.
+    fn covering(&self, p: Point, right: bool, up: bool) -> Option<usize> {
+        let slab = if up {
+            self.ys.partition_point(|&y| y <= p.y)
+        } else {
+            self.ys.partition_point(|&y| y < p.y)
+        }
+        .checked_sub(1)?;
+        let intervals = self.slabs.get(slab)?;
+        let (x_min, x_max, ix) = intervals[if right {
+            intervals.partition_point(|&(x_min, ..)| x_min <= p.x)
+        } else {
+            intervals.partition_point(|&(x_min, ..)| x_min < p.x)
+        }
+        .checked_sub(1)?];
+        let covered = if right {
+            x_min <= p.x && p.x < x_max
+        } else {
+            x_min < p.x && p.x <= x_max
+        };
+        covered.then_some(ix)
+    }
+
+    pub fn locate(&self, p: Point) -> Location {
+        let quadrants =
+            [(true, true), (false, true), (false, false), (true, false)]
+                .map(|(right, up)| self.covering(p, right, up));
+        match quadrants.iter().flatten().next() {
+            None => Location::Outside,
+            Some(&ix) if quadrants.iter().all(Option::is_some) => {
+                Location::Inside(ix)
+            }
+            Some(&ix) => Location::Boundary(ix),
+        }
+    }
+}
+
+impl Decomposer {
+    /// Decompose a polygon, and build a `PointLocator` over its rects.
+    pub fn point_locator(
+        points: Vec<Point>,
+    ) -> Result<PointLocator, DecompErr> {
+        Ok(PointLocator::new(Decomposer::decompose(points)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An L whose foot is `(0, 0, 4, 2)` and whose arm is `(2, 2, 4, 4)`.
+    fn l_shape() -> PointLocator {
+        let points = [(0, 0), (0, 2), (2, 2), (2, 4), (4, 4), (4, 0)]
+            .iter()
+            .map(|&p| p.into())
+            .collect();
+        Decomposer::point_locator(points).unwrap()
+    }
+
+    fn rect_at(locator: &PointLocator, x: isize, y: isize) -> usize {
+        locator
+            .rects()
+            .iter()
+            .position(|rect| rect.contains(&Point::new(x, y)))
+            .unwrap()
+    }
+
+    #[test]
+    fn inside() {
+        let locator = l_shape();
+        let (foot, arm) = (rect_at(&locator, 1, 1), rect_at(&locator, 3, 3));
+        assert_ne!(foot, arm);
+        assert_eq!(locator.locate(Point::new(1, 1)), Location::Inside(foot));
+        assert_eq!(locator.locate(Point::new(3, 3)), Location::Inside(arm));
+        // On the cut between the two rects.
+        assert!(matches!(
+            locator.locate(Point::new(3, 2)),
+            Location::Inside(ix) if ix == foot || ix == arm
+        ));
+    }
+
+    #[test]
+    fn boundary_and_outside() {
+        let locator = l_shape();
+        let arm = rect_at(&locator, 3, 3);
+        assert_eq!(locator.locate(Point::new(2, 3)), Location::Boundary(arm));
+        for (x, y) in [(0, 1), (2, 2), (4, 4), (0, 0), (4, 1)] {
+            assert!(matches!(
+                locator.locate(Point::new(x, y)),
+                Location::Boundary(_)
+            ));
+        }
+        for (x, y) in [(1, 3), (5, 1), (-1, 0), (3, 5), (0, 4)] {
+            assert_eq!(locator.locate(Point::new(x, y)), Location::Outside);
+        }
+    }
+
+    #[test]
+    fn degenerate_rects_are_ignored() {
+        let locator = PointLocator::new(vec![
+            Rect::from_bounds(0, 0, 0, 5),
+            Rect::from_bounds(0, 0, 2, 2),
+        ]);
+        assert_eq!(locator.locate(Point::new(1, 1)), Location::Inside(1));
+        assert_eq!(locator.locate(Point::new(0, 4)), Location::Outside);
+        assert_eq!(
+            PointLocator::default().locate(Point::new(0, 0)),
+            Location::Outside
+        );
+    }
+}
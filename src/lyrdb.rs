@@ -0,0 +1,350 @@
+// Based on:
+// https://www.klayout.de/doc/about/rdb_format.html
+// https://github.com/KLayout/klayout/blob/master/src/rdb/rdb/rdbFile.cc
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+};
+
+/// Category of the markers added for the rects of successful
+/// decompositions.
+pub const RECTS_CATEGORY: &str = "Rects";
+
+/// One value attached to a marker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkerValue {
+    Polygon(Vec<Point>),
+    Box(Rect),
+    Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub category: String,
+    pub cell: String,
+    pub values: Vec<MarkerValue>,
+}
+
+/// A KLayout report database (`.lyrdb`), collecting markers for the polygons
+/// that could not be decomposed and, optionally, for the rects of those that
+/// could.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkerDatabase {
+    pub description: String,
+    pub top_cell: String,
+    /// Database units per micron: markers are written in microns.
+    pub units_per_micron: f64,
+    /// Whether `check` adds a marker for every output rect.
+    pub with_rects: bool,
+    /// Names and descriptions of the categories, in the order they were
+    /// first used.
+    pub categories: Vec<(String, String)>,
+    pub markers: Vec<Marker>,
+}
+
+/// An empty database in the usual 1000 database units per micron.
+impl Default for MarkerDatabase {
+    fn default() -> Self {
+        Self {
+            description: String::new(),
+            top_cell: String::new(),
+            units_per_micron: 1000.0,
+            with_rects: false,
+            categories: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+}
+
+/// What a failure category means, for the category's description.
+fn describe(err: &DecompErr) -> &'static str {
+    match err {
+        DecompErr::NotEnoughPoints => "Polygon has fewer than 3 points",
+        DecompErr::FailedScanlineUpdate => {
+            "Decomposition failed to advance the scanline"
+        }
+        DecompErr::IsAlreadySimple => "Polygon has only 3 points",
+        DecompErr::NotOctilinear => {
+            "Polygon has an edge that is neither horizontal, vertical, nor \
+             diagonal at 45 degrees"
+        }
+        DecompErr::TooManyConcaveVertices => {
+            "Polygon has too many concave vertices for an exhaustive search"
+        }
+        DecompErr::ShotBelowMinimum => {
+            "Polygon cannot be fractured into shots of the minimum size"
+        }
+        DecompErr::InvalidShotRule => {
+            "Maximum shot size is smaller than the writing grid"
+        }
+        DecompErr::NonPositiveBound => "Rect size bound is not positive",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl MarkerDatabase {
+    pub fn new(top_cell: &str, units_per_micron: f64) -> Self {
+        Self {
+            top_cell: top_cell.to_string(),
+            units_per_micron,
+            ..Default::default()
+        }
+    }
+
+    /// Register a category, unless it already exists.
+    pub fn add_category(&mut self, name: &str, description: &str) {
+        if !self.categories.iter().any(|(known, _)| known == name) {
+            self.categories
+                .push((name.to_string(), description.to_string()));
+        }
+    }
+
+    pub fn add_marker(
+        &mut self,
+        category: &str,
+        cell: &str,
+        values: Vec<MarkerValue>,
+    ) {
+        self.add_category(category, "");
+        self.markers.push(Marker {
+            category: category.to_string(),
+            cell: cell.to_string(),
+            values,
+        });
+    }
+
+    /// Mark a polygon that failed to decompose, under the category named
+    /// after the error.
+    pub fn add_error(&mut self, cell: &str, points: &[Point], err: &DecompErr) {
+        let category = format!("{:?}", err);
+        self.add_category(&category, describe(err));
+        self.add_marker(
+            &category,
+            cell,
+            vec![
+                MarkerValue::Polygon(points.to_vec()),
+                MarkerValue::Text(err.to_string()),
+            ],
+        );
+    }
+
+    /// Mark each rect of a decomposition, under `RECTS_CATEGORY`.
+    pub fn add_rects(&mut self, cell: &str, rects: &[Rect]) {
+        self.add_category(RECTS_CATEGORY, "Rects of decomposed polygons");
+        for &rect in rects {
+            self.add_marker(RECTS_CATEGORY, cell, vec![MarkerValue::Box(rect)]);
+        }
+    }
+
+    /// Decompose a polygon of `cell`, marking it if that fails, or its rects
+    /// if `with_rects` is set.
+    pub fn check(
+        &mut self,
+        cell: &str,
+        points: Vec<Point>,
+    ) -> Result<Vec<Rect>, DecompErr> {
+        match Decomposer::decompose(points.clone()) {
+            Ok(rects) => {
+                if self.with_rects {
+                    self.add_rects(cell, &rects);
+                }
+                Ok(rects)
+            }
+            Err(err) => {
+                self.add_error(cell, &points, &err);
+                Err(err)
+            }
+        }
+    }
+
+    fn point(&self, p: &Point) -> String {
+        format!(
+            "{},{}",
+            p.x as f64 / self.units_per_micron,
+            p.y as f64 / self.units_per_micron
+        )
+    }
+
+    fn value(&self, value: &MarkerValue) -> String {
+        match value {
+            MarkerValue::Polygon(points) => format!(
+                "polygon: ({})",
+                points
+                    .iter()
+                    .map(|p| self.point(p))
+                    .collect::<Vec<String>>()
+                    .join(";")
+            ),
+            MarkerValue::Box(rect) => format!(
+                "box: ({};{})",
+                self.point(&rect.left()),
+                self.point(&rect.right())
+            ),
+            MarkerValue::Text(text) => format!(
+                "text: '{}'",
+                text.replace('\\', "\\\\").replace('\'', "\\'")
+            ),
+        }
+    }
+
+    /// Write the database as `.lyrdb` XML, with cells listed in the order
+    /// they were first used by a marker.
+    pub fn to_xml(&self) -> String {
+        let mut cells: Vec<&str> = Vec::new();
+        for marker in &self.markers {
+            if !cells.contains(&marker.cell.as_str()) {
+                cells.push(&marker.cell);
+            }
+        }
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<report-database>\n");
+        xml.push_str(&format!(
+            " <description>{}</description>\n",
+            escape(&self.description)
+        ));
+        xml.push_str(" <original-file/>\n");
+        xml.push_str(" <generator>odb_poly_decomp</generator>\n");
+        xml.push_str(&format!(
+            " <top-cell>{}</top-cell>\n",
+            escape(&self.top_cell)
+        ));
+        xml.push_str(" <tags/>\n");
+
+        xml.push_str(" <categories>\n");
+        for (name, description) in &self.categories {
+            xml.push_str("  <category>\n");
+            xml.push_str(&format!("   <name>{}</name>\n", escape(name)));
+            xml.push_str(&format!(
+                "   <description>{}</description>\n",
+                escape(description)
+            ));
+            xml.push_str("   <categories/>\n");
+            xml.push_str("  </category>\n");
+        }
+        xml.push_str(" </categories>\n");
+
+        xml.push_str(" <cells>\n");
+        for cell in &cells {
+            xml.push_str("  <cell>\n");
+            xml.push_str(&format!("   <name>{}</name>\n", escape(cell)));
+            xml.push_str("   <variant/>\n");
+            xml.push_str("   <references/>\n");
+            xml.push_str("  </cell>\n");
+        }
+        xml.push_str(" </cells>\n");
+
+        // Items are grouped by category, then by cell.
+        xml.push_str(" <items>\n");
+        for (category, _) in &self.categories {
+            for cell in &cells {
+                for marker in self.markers.iter().filter(|marker| {
+                    marker.category == *category && marker.cell == *cell
+                }) {
+                    xml.push_str("  <item>\n");
+                    xml.push_str("   <tags/>\n");
+                    xml.push_str(&format!(
+                        "   <category>{}</category>\n",
+                        escape(&marker.category)
+                    ));
+                    xml.push_str(&format!(
+                        "   <cell>{}</cell>\n",
+                        escape(&marker.cell)
+                    ));
+                    xml.push_str("   <visited>false</visited>\n");
+                    xml.push_str("   <multiplicity>1</multiplicity>\n");
+                    xml.push_str("   <values>\n");
+                    for value in &marker.values {
+                        xml.push_str(&format!(
+                            "    <value>{}</value>\n",
+                            escape(&self.value(value))
+                        ));
+                    }
+                    xml.push_str("   </values>\n");
+                    xml.push_str("  </item>\n");
+                }
+            }
+        }
+        xml.push_str(" </items>\n");
+        xml.push_str("</report-database>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn default_is_in_nanometers() {
+        let db = MarkerDatabase::default();
+        assert_eq!(db.units_per_micron, 1000.0);
+        assert_eq!(db.point(&Point::new(1500, -20)), "1.5,-0.02");
+    }
+
+    #[test]
+    fn failures_are_marked() {
+        let mut db = MarkerDatabase::new("top", 1000.0);
+        let points = ring(&[(0, 0), (0, 1000)]);
+        assert_eq!(
+            db.check("a", points.clone()),
+            Err(DecompErr::NotEnoughPoints)
+        );
+        assert_eq!(
+            db.categories,
+            vec![(
+                "NotEnoughPoints".to_string(),
+                "Polygon has fewer than 3 points".to_string()
+            )]
+        );
+        assert_eq!(db.markers.len(), 1);
+        assert_eq!(db.markers[0].values[0], MarkerValue::Polygon(points));
+
+        let l = ring(&[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]);
+        assert_eq!(db.check("a", l.clone()).unwrap().len(), 2);
+        assert_eq!(db.markers.len(), 1);
+        db.with_rects = true;
+        db.check("b", l).unwrap();
+        assert_eq!(db.markers.len(), 3);
+        assert_eq!(db.categories[1].0, RECTS_CATEGORY);
+    }
+
+    #[test]
+    fn xml() {
+        let mut db = MarkerDatabase::new("top & co", 1000.0);
+        db.add_marker(
+            "notes",
+            "<cell>",
+            vec![
+                MarkerValue::Box(Rect::from_bounds(0, 0, 500, 2000)),
+                MarkerValue::Text("it's".to_string()),
+            ],
+        );
+        let xml = db.to_xml();
+        assert!(xml.contains("<top-cell>top &amp; co</top-cell>"));
+        assert!(xml.contains("<cell>&lt;cell&gt;</cell>"));
+        assert!(xml.contains("<value>box: (0,0;0.5,2)</value>"));
+        assert!(xml.contains("<value>text: &apos;it\\&apos;s&apos;</value>"));
+    }
+}
@@ -1,6 +1,61 @@
 use odb_poly_decomp::{decomposer::Decomposer, point::Point};
 
+/// Decompose a batch of polygons given as JSON Lines (see
+/// `odb_poly_decomp::jsonl`), reading from `input` and writing to `output`.
+/// Either defaults to the standard streams when missing or `-`.
+///
+/// Malformed lines are reported on stderr after the rest of the batch, and
+/// counted in the result.
+#[cfg(feature = "serde")]
+fn decompose_jsonl(
+    input: Option<String>,
+    output: Option<String>,
+) -> serde_json::Result<usize> {
+    use std::{
+        fs::File,
+        io::{self, BufRead, BufReader, BufWriter, Write},
+    };
+
+    use odb_poly_decomp::jsonl;
+
+    let reader: Box<dyn BufRead> = match input.as_deref() {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).map_err(serde_json::Error::io)?,
+        )),
+    };
+    let mut writer: Box<dyn Write> = match output.as_deref() {
+        None | Some("-") => Box::new(BufWriter::new(io::stdout().lock())),
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(serde_json::Error::io)?,
+        )),
+    };
+    let malformed = jsonl::decompose_lines(reader, &mut writer)?;
+    writer.flush().map_err(serde_json::Error::io)?;
+    for line in &malformed {
+        eprintln!("line {}: {}", line.line, line.error);
+    }
+    Ok(malformed.len())
+}
+
 pub fn main() {
+    // `--jsonl [input] [output]` decomposes a batch instead of the example
+    // below. Tracing is left off, since it would write to stdout too.
+    #[cfg(feature = "serde")]
+    {
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() == Some("--jsonl") {
+            match decompose_jsonl(args.next(), args.next()) {
+                Ok(0) => return,
+                Ok(_) => std::process::exit(1),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
     tracing_subscriber::fmt()
         .pretty()
         // enable everything
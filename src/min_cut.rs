@@ -0,0 +1,630 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    geometry::Geometry,
+    grid::CompressedGrid,
+    point::Point,
+    polygon::{Polygon, Winding},
+    rect::Rect,
+};
+
+/// The most concave vertices `CutSearch::Exhaustive` accepts, and below
+/// which `CutSearch::Auto` uses it.
+pub const EXHAUSTIVE_LIMIT: usize = 16;
+
+/// How the cut directions at the concave vertices are picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CutSearch {
+    /// Try every assignment of cut directions to the concave vertices, and
+    /// keep the one whose grown cuts are the shortest. The cost doubles with
+    /// each concave vertex, so polygons with more than `EXHAUSTIVE_LIMIT` of
+    /// them are rejected.
+    Exhaustive,
+    /// Resolve as many concave vertices as possible in pairs, with a single
+    /// chord joining two of them, and extend the others along their shorter
+    /// cut.
+    Heuristic,
+    /// `Exhaustive` for polygons with at most `EXHAUSTIVE_LIMIT` concave
+    /// vertices, `Heuristic` otherwise.
+    Auto,
+}
+
+/// A partition into rects, chosen for its total internal cut length rather
+/// than for its rect count.
+#[derive(Clone, Debug, Default)]
+pub struct MinCutPartition {
+    pub rects: Vec<Rect>,
+    /// Sum of the lengths of all cuts inside the polygon.
+    pub cut_length: isize,
+    /// Whether every assignment of cut directions was tried. Partitions with
+    /// cuts that do not start at a concave vertex, or that cross other cuts,
+    /// are never considered, so even then `cut_length` is not necessarily
+    /// the least over all partitions.
+    pub exhaustive: bool,
+}
+
+/// A cut from a concave vertex, running into the polygon until it meets its
+/// boundary or another cut. Coordinates are doubled, so that two cuts meeting
+/// head-on can stop halfway.
+#[derive(Clone, Copy, Debug)]
+struct Ray {
+    origin: Point,
+    direction: (isize, isize),
+    /// How far the ray can go before meeting the boundary.
+    reach: isize,
+}
+
+impl Ray {
+    fn new(ring: &[Point], origin: Point, direction: (isize, isize)) -> Self {
+        let reach = ring
+            .iter()
+            .zip(ring.iter().cycle().skip(1))
+            .filter_map(|(p, q)| {
+                let (x_min, x_max) = (p.x.min(q.x), p.x.max(q.x));
+                let (y_min, y_max) = (p.y.min(q.y), p.y.max(q.y));
+                let distance = match direction {
+                    (dx, 0) if x_min == x_max => (y_min <= origin.y
+                        && origin.y <= y_max)
+                        .then_some((p.x - origin.x) * dx),
+                    (0, dy) if y_min == y_max => (x_min <= origin.x
+                        && origin.x <= x_max)
+                        .then_some((p.y - origin.y) * dy),
+                    _ => None,
+                };
+                distance.filter(|&d| d > 0)
+            })
+            .min()
+            .expect("a ray into a closed polygon should meet its boundary");
+        Self {
+            origin,
+            direction,
+            reach,
+        }
+    }
+
+    #[inline]
+    fn at(&self, distance: isize) -> Point {
+        Point::new(
+            self.origin.x + self.direction.0 * distance,
+            self.origin.y + self.direction.1 * distance,
+        )
+    }
+
+    #[inline]
+    fn is_horizontal(&self) -> bool {
+        self.direction.1 == 0
+    }
+}
+
+/// The two candidate cuts at each concave vertex of a clockwise ring: the
+/// extensions of its incoming edge and of its (reversed) outgoing edge.
+fn candidate_rays(ring: &[Point]) -> Vec<[Ray; 2]> {
+    let n = ring.len();
+    (0..n)
+        .filter_map(|ix| {
+            let (prev, here, next) =
+                (ring[(ix + n - 1) % n], ring[ix], ring[(ix + 1) % n]);
+            let incoming =
+                ((here.x - prev.x).signum(), (here.y - prev.y).signum());
+            let outgoing =
+                ((next.x - here.x).signum(), (next.y - here.y).signum());
+            // Going clockwise, the polygon turns left at concave vertices.
+            (incoming.0 * outgoing.1 - incoming.1 * outgoing.0 > 0).then(|| {
+                [
+                    Ray::new(ring, here, incoming),
+                    Ray::new(ring, here, (-outgoing.0, -outgoing.1)),
+                ]
+            })
+        })
+        .collect()
+}
+
+/// Grow all rays at the same speed, each stopping at the boundary or at the
+/// first cut another ray has already laid down. Returns how far each got.
+fn grow(rays: &[Ray]) -> Vec<isize> {
+    // (time r stops, r, s, time s gets there, whether they meet head-on)
+    let mut events = Vec::new();
+    for (r, ray) in rays.iter().enumerate() {
+        for (s, other) in rays.iter().enumerate() {
+            if r == s {
+                continue;
+            }
+            if ray.is_horizontal() != other.is_horizontal() {
+                let meet = if ray.is_horizontal() {
+                    Point::new(other.origin.x, ray.origin.y)
+                } else {
+                    Point::new(ray.origin.x, other.origin.y)
+                };
+                let along = |ray: &Ray| {
+                    (meet.x - ray.origin.x) * ray.direction.0
+                        + (meet.y - ray.origin.y) * ray.direction.1
+                };
+                let (t_r, t_s) = (along(ray), along(other));
+                if 0 < t_r && t_r <= ray.reach && 0 <= t_s && t_s <= other.reach
+                {
+                    events.push((t_r, r, s, t_s, false));
+                }
+            } else if ray.direction.0 == -other.direction.0
+                && ray.direction.1 == -other.direction.1
+            {
+                let gap = (other.origin.x - ray.origin.x) * ray.direction.0
+                    + (other.origin.y - ray.origin.y) * ray.direction.1;
+                let collinear = if ray.is_horizontal() {
+                    ray.origin.y == other.origin.y
+                } else {
+                    ray.origin.x == other.origin.x
+                };
+                if collinear && gap > 0 && gap <= ray.reach {
+                    events.push((gap / 2, r, s, gap / 2, true));
+                }
+            }
+        }
+    }
+    events.sort_unstable_by_key(|&(t_r, r, ..)| (t_r, r));
+
+    let mut stops: Vec<Option<isize>> = vec![None; rays.len()];
+    for (t_r, r, s, t_s, head_on) in events {
+        let arrived_first = head_on || (t_s, s) < (t_r, r);
+        // Any event stopping `s` before `t_s` came earlier, since `t_s <=
+        // t_r`.
+        let still_growing = stops[s].is_none_or(|stop| stop >= t_s);
+        if stops[r].is_none() && arrived_first && still_growing {
+            stops[r] = Some(t_r);
+        }
+    }
+    stops
+        .iter()
+        .zip(rays)
+        .map(|(stop, ray)| stop.unwrap_or(ray.reach))
+        .collect()
+}
+
+fn chosen(candidates: &[[Ray; 2]], choices: &[usize]) -> Vec<Ray> {
+    candidates
+        .iter()
+        .zip(choices)
+        .map(|(pair, &choice)| pair[choice])
+        .collect()
+}
+
+#[inline]
+fn total(candidates: &[[Ray; 2]], choices: &[usize]) -> isize {
+    grow(&chosen(candidates, choices)).iter().sum()
+}
+
+fn search_exhaustive(candidates: &[[Ray; 2]]) -> Vec<usize> {
+    let k = candidates.len();
+    debug_assert!(k <= EXHAUSTIVE_LIMIT);
+    (0..1_usize << k)
+        .map(|mask| (0..k).map(|ix| (mask >> ix) & 1).collect::<Vec<_>>())
+        .min_by_key(|choices| total(candidates, choices))
+        .unwrap_or_default()
+}
+
+/// A cut joining two concave vertices: `(vertex, candidate)` at both ends.
+#[derive(Clone, Copy, Debug)]
+struct Chord {
+    ends: [(usize, usize); 2],
+    /// The cut as a rect, degenerate along its direction.
+    span: Rect,
+}
+
+/// The chords of the polygon: candidate cuts running from one concave vertex
+/// straight to another, which then has a candidate cut running back.
+fn chords(candidates: &[[Ray; 2]]) -> Vec<Chord> {
+    let vertices = candidates
+        .iter()
+        .enumerate()
+        .map(|(vertex, pair)| (pair[0].origin, vertex))
+        .collect::<BTreeMap<Point, usize>>();
+    let mut chords = Vec::new();
+    for (vertex, pair) in candidates.iter().enumerate() {
+        for (candidate, ray) in pair.iter().enumerate() {
+            let end = ray.at(ray.reach);
+            let Some(&other) = vertices.get(&end) else {
+                continue;
+            };
+            let back = candidates[other].iter().position(|back| {
+                back.direction == (-ray.direction.0, -ray.direction.1)
+            });
+            if let Some(back) = back.filter(|_| vertex < other) {
+                chords.push(Chord {
+                    ends: [(vertex, candidate), (other, back)],
+                    span: Rect::new(ray.origin.min(end), ray.origin.max(end)),
+                });
+            }
+        }
+    }
+    chords
+}
+
+/// Find an augmenting path from the horizontal chord `h` (Kuhn's algorithm).
+fn augment(
+    h: usize,
+    conflicts: &[Vec<usize>],
+    visited: &mut [bool],
+    matched: &mut [Option<usize>],
+) -> bool {
+    for &v in &conflicts[h] {
+        if visited[v] {
+            continue;
+        }
+        visited[v] = true;
+        if matched[v]
+            .is_none_or(|other| augment(other, conflicts, visited, matched))
+        {
+            matched[v] = Some(h);
+            return true;
+        }
+    }
+    false
+}
+
+/// A largest set of chords no two of which cross or share a vertex.
+///
+/// Only a horizontal and a vertical chord can conflict, so the conflicts
+/// form a bipartite graph, and the chords left out are a minimum vertex cover
+/// of it, found from a maximum matching (König's theorem).
+fn independent_chords(chords: &[Chord]) -> Vec<Chord> {
+    let (horizontal, vertical): (Vec<Chord>, Vec<Chord>) =
+        chords.iter().partition(|chord| chord.span.height() == 0);
+    let conflicts = horizontal
+        .iter()
+        .map(|h| {
+            (0..vertical.len())
+                .filter(|&v| h.span.intersects(&vertical[v].span))
+                .collect::<Vec<usize>>()
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut matched = vec![None; vertical.len()];
+    for h in 0..horizontal.len() {
+        augment(
+            h,
+            &conflicts,
+            &mut vec![false; vertical.len()],
+            &mut matched,
+        );
+    }
+
+    // Chords reachable from unmatched horizontal ones by alternating paths.
+    let mut reached_h = vec![false; horizontal.len()];
+    let mut reached_v = vec![false; vertical.len()];
+    let mut queue = (0..horizontal.len())
+        .filter(|&h| !matched.contains(&Some(h)))
+        .collect::<VecDeque<usize>>();
+    for &h in &queue {
+        reached_h[h] = true;
+    }
+    while let Some(h) = queue.pop_front() {
+        for &v in &conflicts[h] {
+            if reached_v[v] {
+                continue;
+            }
+            reached_v[v] = true;
+            if let Some(next) = matched[v].filter(|&next| !reached_h[next]) {
+                reached_h[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let kept_h = (0..horizontal.len())
+        .filter(|&h| reached_h[h])
+        .map(|h| horizontal[h]);
+    let kept_v = (0..vertical.len())
+        .filter(|&v| !reached_v[v])
+        .map(|v| vertical[v]);
+    kept_h.chain(kept_v).collect()
+}
+
+fn search_chords(candidates: &[[Ray; 2]]) -> Vec<usize> {
+    let mut choices = candidates
+        .iter()
+        .map(|pair| usize::from(pair[1].reach < pair[0].reach))
+        .collect::<Vec<usize>>();
+    for chord in independent_chords(&chords(candidates)) {
+        for (vertex, candidate) in chord.ends {
+            choices[vertex] = candidate;
+        }
+    }
+    choices
+}
+
+/// Cut a (doubled) ring along the given rays, and collect the resulting rects
+/// in the original coordinates.
+fn rects_from_cuts(ring: &[Point], rays: &[Ray], stops: &[isize]) -> Vec<Rect> {
+    let ends = rays
+        .iter()
+        .zip(stops)
+        .map(|(ray, &stop)| ray.at(stop))
+        .collect::<Vec<Point>>();
+    let grid = CompressedGrid::new(
+        ring,
+        ends.iter().map(|p| p.x),
+        ends.iter().map(|p| p.y),
+    );
+    let (columns, rows) = (grid.columns(), grid.rows());
+
+    // A horizontal cut separates vertically adjacent cells, and vice versa:
+    // `(column, row, true)` blocks cell `(column, row)` from the one below,
+    // `(column, row, false)` from the one on its left.
+    let mut blocked = HashSet::new();
+    for (ray, end) in rays.iter().zip(ends.iter()) {
+        let (start, end) = (ray.origin.min(*end), ray.origin.max(*end));
+        if ray.is_horizontal() {
+            let row = grid.row_of(start.y).unwrap();
+            for column in 0..columns {
+                if start.x <= grid.xs()[column]
+                    && grid.xs()[column + 1] <= end.x
+                {
+                    blocked.insert((column, row, true));
+                }
+            }
+        } else {
+            let column = grid.column_of(start.x).unwrap();
+            for row in 0..rows {
+                if start.y <= grid.ys()[row] && grid.ys()[row + 1] <= end.y {
+                    blocked.insert((column, row, false));
+                }
+            }
+        }
+    }
+
+    let mut seen = vec![false; columns * rows];
+    let mut rects = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            if seen[row * columns + column] || !grid.is_inside(column, row) {
+                continue;
+            }
+            seen[row * columns + column] = true;
+            let (mut low, mut high) = ((column, row), (column, row));
+            let mut queue = VecDeque::from([(column, row)]);
+            while let Some((c, r)) = queue.pop_front() {
+                low = (low.0.min(c), low.1.min(r));
+                high = (high.0.max(c), high.1.max(r));
+                let neighbours = [
+                    (c > 0 && !blocked.contains(&(c, r, false)))
+                        .then(|| (c - 1, r)),
+                    (c + 1 < columns && !blocked.contains(&(c + 1, r, false)))
+                        .then(|| (c + 1, r)),
+                    (r > 0 && !blocked.contains(&(c, r, true)))
+                        .then(|| (c, r - 1)),
+                    (r + 1 < rows && !blocked.contains(&(c, r + 1, true)))
+                        .then(|| (c, r + 1)),
+                ];
+                for (nc, nr) in neighbours.into_iter().flatten() {
+                    if !seen[nr * columns + nc] && grid.is_inside(nc, nr) {
+                        seen[nr * columns + nc] = true;
+                        queue.push_back((nc, nr));
+                    }
+                }
+            }
+            rects.push(Rect::from_bounds(
+                grid.xs()[low.0] / 2,
+                grid.ys()[low.1] / 2,
+                grid.xs()[high.0 + 1] / 2,
+                grid.ys()[high.1 + 1] / 2,
+            ));
+        }
+    }
+    rects
+}
+
+impl Decomposer {
+    /// Partition a polygon into rects, picking its cuts to keep their total
+    /// length inside it short, rather than the number of rects low. The
+    /// polygon may be given in either orientation: it is made clockwise
+    /// first.
+    ///
+    /// Every concave vertex of the polygon (as found among the nodes of its
+    /// `Geometry`) needs a cut, running along the extension of one of its two
+    /// edges. Once a direction is picked for each, the cuts are grown
+    /// simultaneously, each stopping at the boundary or at the first cut it
+    /// meets, which always leaves a partition into rects. Two concave
+    /// vertices whose cuts run towards each other share a chord, which
+    /// resolves both for the length of one cut. `search` decides how the
+    /// directions are picked: see `CutSearch`. Neither way is guaranteed to
+    /// find the partition with the shortest cuts.
+    ///
+    /// Returns `DecompErr::TooManyConcaveVertices` if `search` is
+    /// `Exhaustive` and the polygon has more than `EXHAUSTIVE_LIMIT` concave
+    /// vertices.
+    pub fn decompose_min_cut(
+        mut points: Vec<Point>,
+        search: CutSearch,
+    ) -> Result<MinCutPartition, DecompErr> {
+        if Polygon::from(points.as_slice()).orientation()
+            == Some(Winding::CounterClockwise)
+        {
+            points.reverse();
+        }
+        let geometry = Geometry::new(points)?;
+        let ring = geometry
+            .boundary()
+            .iter()
+            .map(|p| Point::new(2 * p.x, 2 * p.y))
+            .collect::<Vec<Point>>();
+
+        let candidates = candidate_rays(&ring);
+        let exhaustive = match search {
+            CutSearch::Exhaustive if candidates.len() > EXHAUSTIVE_LIMIT => {
+                return Err(DecompErr::TooManyConcaveVertices);
+            }
+            CutSearch::Exhaustive => true,
+            CutSearch::Heuristic => false,
+            CutSearch::Auto => candidates.len() <= EXHAUSTIVE_LIMIT,
+        };
+        let choices = if exhaustive {
+            search_exhaustive(&candidates)
+        } else {
+            search_chords(&candidates)
+        };
+
+        let rays = chosen(&candidates, &choices);
+        let stops = grow(&rays);
+        Ok(MinCutPartition {
+            rects: rects_from_cuts(&ring, &rays, &stops),
+            cut_length: stops.iter().sum::<isize>() / 2,
+            exhaustive,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// A staircase of `steps` steps, with `steps - 1` concave vertices.
+    fn staircase(steps: isize) -> Vec<Point> {
+        let mut points = vec![Point::new(0, 0), Point::new(0, steps)];
+        for step in 1..steps {
+            points.push(Point::new(step, steps - step + 1));
+            points.push(Point::new(step, steps - step));
+        }
+        points.extend([Point::new(steps, 1), Point::new(steps, 0)]);
+        points
+    }
+
+    fn check_partition(partition: &MinCutPartition, area: i128) {
+        assert_eq!(partition.rects.iter().map(Rect::area).sum::<i128>(), area);
+        for (ix, a) in partition.rects.iter().enumerate() {
+            for b in &partition.rects[ix + 1..] {
+                assert!(
+                    a.x_max() <= b.x_min()
+                        || b.x_max() <= a.x_min()
+                        || a.y_max() <= b.y_min()
+                        || b.y_max() <= a.y_min()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn l_shape() {
+        let points = ring(&[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]);
+        for search in [CutSearch::Exhaustive, CutSearch::Heuristic] {
+            let partition =
+                Decomposer::decompose_min_cut(points.clone(), search).unwrap();
+            assert_eq!(partition.cut_length, 1);
+            assert_eq!(partition.rects.len(), 2);
+            assert_eq!(partition.exhaustive, search == CutSearch::Exhaustive);
+            check_partition(&partition, 3);
+        }
+    }
+
+    #[test]
+    fn counter_clockwise_input() {
+        let mut points =
+            ring(&[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]);
+        points.reverse();
+        for search in [CutSearch::Exhaustive, CutSearch::Heuristic] {
+            let partition =
+                Decomposer::decompose_min_cut(points.clone(), search).unwrap();
+            assert_eq!(partition.cut_length, 1);
+            assert_eq!(partition.rects.len(), 2);
+            check_partition(&partition, 3);
+        }
+    }
+
+    #[test]
+    fn facing_vertices_share_a_chord() {
+        // An H: the two chords across the middle bar cost 1 each.
+        let points = ring(&[
+            (0, 0),
+            (0, 3),
+            (1, 3),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (3, 0),
+            (2, 0),
+            (2, 1),
+            (1, 1),
+            (1, 0),
+        ]);
+        for search in [CutSearch::Exhaustive, CutSearch::Heuristic] {
+            let partition =
+                Decomposer::decompose_min_cut(points.clone(), search).unwrap();
+            assert_eq!(partition.cut_length, 2);
+            assert_eq!(partition.rects.len(), 3);
+            check_partition(&partition, 7);
+        }
+    }
+
+    #[test]
+    fn crossing_chords_are_not_both_used() {
+        let plus = ring(&[
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (2, 2),
+            (3, 2),
+            (3, 1),
+            (2, 1),
+            (2, 0),
+        ]);
+        let ring = plus
+            .iter()
+            .map(|p| Point::new(2 * p.x, 2 * p.y))
+            .collect::<Vec<Point>>();
+        let candidates = candidate_rays(&ring);
+        assert_eq!(chords(&candidates).len(), 4);
+        assert_eq!(independent_chords(&chords(&candidates)).len(), 2);
+
+        let partition =
+            Decomposer::decompose_min_cut(plus, CutSearch::Heuristic).unwrap();
+        assert_eq!(partition.cut_length, 2);
+        assert_eq!(partition.rects.len(), 3);
+        check_partition(&partition, 5);
+    }
+
+    #[test]
+    fn heuristic_is_bounded_by_exhaustive() {
+        let exhaustive =
+            Decomposer::decompose_min_cut(staircase(6), CutSearch::Exhaustive)
+                .unwrap();
+        let heuristic =
+            Decomposer::decompose_min_cut(staircase(6), CutSearch::Heuristic)
+                .unwrap();
+        assert!(exhaustive.cut_length <= heuristic.cut_length);
+        check_partition(&exhaustive, 21);
+        check_partition(&heuristic, 21);
+    }
+
+    #[test]
+    fn exhaustive_rejects_large_polygons() {
+        let points = staircase(EXHAUSTIVE_LIMIT as isize + 2);
+        assert_eq!(
+            Decomposer::decompose_min_cut(
+                points.clone(),
+                CutSearch::Exhaustive
+            )
+            .unwrap_err(),
+            DecompErr::TooManyConcaveVertices
+        );
+        let auto =
+            Decomposer::decompose_min_cut(points, CutSearch::Auto).unwrap();
+        assert!(!auto.exhaustive);
+        check_partition(&auto, 18 * 19 / 2);
+    }
+
+    #[test]
+    fn heuristic_scales() {
+        let partition =
+            Decomposer::decompose_min_cut(staircase(300), CutSearch::Heuristic)
+                .unwrap();
+        check_partition(&partition, 300 * 301 / 2);
+    }
+}
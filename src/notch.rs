@@ -0,0 +1,250 @@
+use crate::{
+    geometry::Geometry, outline::drop_collinear, point::Point, rect::Rect,
+};
+
+/// The turn a clockwise polygon's boundary takes at a vertex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    /// The interior angle is 90 degrees: the boundary turns right.
+    Convex,
+    /// The interior angle is 270 degrees: the boundary turns left.
+    Concave,
+}
+
+/// A gap in the boundary of a polygon, between two parallel edges facing
+/// each other across the outside, narrower than the notch rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotchViolation {
+    /// The edge at the bottom of the notch, between its two concave corners.
+    pub edge: (Point, Point),
+    /// The part of the gap where both sides face each other.
+    pub marker: Rect,
+    /// The distance between the two sides, i.e. the length of `edge`.
+    pub width: isize,
+    /// The length of the shorter side.
+    pub depth: isize,
+}
+
+/// An edge between two convex corners shorter than the end-of-line width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EndOfLine {
+    pub edge: (Point, Point),
+    pub length: isize,
+}
+
+#[inline]
+fn length(a: Point, b: Point) -> isize {
+    (b.x - a.x).abs() + (b.y - a.y).abs()
+}
+
+/// The corner at each vertex of a clockwise rectilinear ring, which must not
+/// have collinear vertices.
+pub fn corners(ring: &[Point]) -> Vec<Corner> {
+    let n = ring.len();
+    (0..n)
+        .map(|ix| {
+            let (prev, here, next) =
+                (ring[(ix + n - 1) % n], ring[ix], ring[(ix + 1) % n]);
+            let cross = (here.x - prev.x) as i128 * (next.y - here.y) as i128
+                - (here.y - prev.y) as i128 * (next.x - here.x) as i128;
+            if cross < 0 {
+                Corner::Convex
+            } else {
+                Corner::Concave
+            }
+        })
+        .collect()
+}
+
+/// An edge of a ring, from `a` to `b`.
+struct RingEdge {
+    a: Point,
+    b: Point,
+    corners: (Corner, Corner),
+    /// The vertex before `a`, and the one after `b`.
+    before: Point,
+    after: Point,
+}
+
+impl RingEdge {
+    #[inline]
+    fn length(&self) -> isize {
+        length(self.a, self.b)
+    }
+}
+
+/// The edges of the boundary of `geometry`, without collinear vertices.
+fn edges(geometry: &Geometry) -> Vec<RingEdge> {
+    let ring = drop_collinear(geometry.boundary());
+    let corners = corners(&ring);
+    let n = ring.len();
+    (0..n)
+        .map(|ix| {
+            let next = (ix + 1) % n;
+            RingEdge {
+                a: ring[ix],
+                b: ring[next],
+                corners: (corners[ix], corners[next]),
+                before: ring[(ix + n - 1) % n],
+                after: ring[(ix + 2) % n],
+            }
+        })
+        .collect()
+}
+
+impl Geometry {
+    /// The corner at each vertex of the polygon, in ring order, with
+    /// collinear vertices and the nodes `split_edge` adds left out.
+    pub fn corners(&self) -> Vec<(Point, Corner)> {
+        let ring = drop_collinear(self.boundary());
+        let corners = corners(&ring);
+        ring.into_iter().zip(corners).collect()
+    }
+
+    /// Find the notches narrower than `min_width`: edges between two concave
+    /// corners, whose neighbouring edges then run parallel to each other away
+    /// from the polygon, as the two sides of a U-shaped gap.
+    ///
+    /// Only gaps whose sides are joined by a single edge are notches; wider
+    /// cavities with steps at their bottom are left to the spacing check.
+    pub fn notches(&self, min_width: isize) -> Vec<NotchViolation> {
+        edges(self)
+            .into_iter()
+            .filter(|edge| {
+                edge.corners == (Corner::Concave, Corner::Concave)
+                    && edge.length() < min_width
+            })
+            .map(|edge| {
+                let RingEdge {
+                    a,
+                    b,
+                    before,
+                    after,
+                    ..
+                } = edge;
+                let depth = length(before, a).min(length(b, after));
+                let (dx, dy) =
+                    ((before.x - a.x).signum(), (before.y - a.y).signum());
+                let opening = Point::new(b.x + dx * depth, b.y + dy * depth);
+                NotchViolation {
+                    edge: (a, b),
+                    marker: Rect::bounding(&[a, opening]),
+                    width: edge.length(),
+                    depth,
+                }
+            })
+            .collect()
+    }
+
+    /// Find the end-of-line edges: edges between two convex corners shorter
+    /// than `eol_width`, as at the end of a wire.
+    pub fn end_of_lines(&self, eol_width: isize) -> Vec<EndOfLine> {
+        edges(self)
+            .into_iter()
+            .filter(|edge| {
+                edge.corners == (Corner::Convex, Corner::Convex)
+                    && edge.length() < eol_width
+            })
+            .map(|edge| EndOfLine {
+                edge: (edge.a, edge.b),
+                length: edge.length(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// A U whose 1-wide notch is 2 deep.
+    fn u_shape() -> Geometry {
+        Geometry::new(ring(&[
+            (0, 0),
+            (0, 3),
+            (1, 3),
+            (1, 1),
+            (2, 1),
+            (2, 3),
+            (3, 3),
+            (3, 0),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn corners_of_a_u() {
+        let corners = u_shape()
+            .corners()
+            .into_iter()
+            .filter(|&(_, corner)| corner == Corner::Concave)
+            .map(|(p, _)| p)
+            .collect::<Vec<Point>>();
+        assert_eq!(corners, ring(&[(1, 1), (2, 1)]));
+    }
+
+    #[test]
+    fn notch() {
+        let geometry = u_shape();
+        assert_eq!(
+            geometry.notches(2),
+            vec![NotchViolation {
+                edge: (Point::new(1, 1), Point::new(2, 1)),
+                marker: Rect::from_bounds(1, 1, 2, 3),
+                width: 1,
+                depth: 2,
+            }]
+        );
+        assert!(geometry.notches(1).is_empty());
+    }
+
+    #[test]
+    fn depth_stops_at_a_step() {
+        let geometry = Geometry::new(ring(&[
+            (0, 0),
+            (0, 4),
+            (1, 4),
+            (1, 2),
+            (2, 2),
+            (2, 1),
+            (3, 1),
+            (3, 4),
+            (4, 4),
+            (4, 0),
+        ]))
+        .unwrap();
+        // Only the bottom of the cavity, below the step, is a notch.
+        assert_eq!(
+            geometry.notches(10),
+            vec![NotchViolation {
+                edge: (Point::new(2, 1), Point::new(3, 1)),
+                marker: Rect::from_bounds(2, 1, 3, 2),
+                width: 1,
+                depth: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn end_of_lines() {
+        let geometry = u_shape();
+        assert_eq!(
+            geometry.end_of_lines(2),
+            vec![
+                EndOfLine {
+                    edge: (Point::new(0, 3), Point::new(1, 3)),
+                    length: 1,
+                },
+                EndOfLine {
+                    edge: (Point::new(2, 3), Point::new(3, 3)),
+                    length: 1,
+                },
+            ]
+        );
+        assert_eq!(geometry.end_of_lines(4).len(), 5);
+    }
+}
@@ -0,0 +1,1277 @@
+// Based on:
+// SEMI P39-0416, "Specification for the OASIS Format"
+// https://github.com/KLayout/klayout/blob/master/src/plugins/streamers/oasis/db_plugin/dbOASISReader.cc
+
+use std::{collections::HashMap, error::Error, fmt::Display};
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+    shape::{normalize_ring, ShapeErr},
+    transform::{Orientation, Transform},
+};
+
+const MAGIC: &[u8] = b"%SEMI-OASIS\r\n";
+
+const PAD: u8 = 0;
+const START: u8 = 1;
+const END: u8 = 2;
+const CELLNAME_IMPLICIT: u8 = 3;
+const CELLNAME: u8 = 4;
+const TEXTSTRING_IMPLICIT: u8 = 5;
+const TEXTSTRING: u8 = 6;
+const PROPNAME_IMPLICIT: u8 = 7;
+const PROPNAME: u8 = 8;
+const PROPSTRING_IMPLICIT: u8 = 9;
+const PROPSTRING: u8 = 10;
+const LAYERNAME: u8 = 11;
+const LAYERNAME_TEXT: u8 = 12;
+const CELL_REF: u8 = 13;
+const CELL_NAME: u8 = 14;
+const XYABSOLUTE: u8 = 15;
+const XYRELATIVE: u8 = 16;
+const PLACEMENT: u8 = 17;
+const PLACEMENT_MAG_ANGLE: u8 = 18;
+const TEXT: u8 = 19;
+const RECTANGLE: u8 = 20;
+const POLYGON: u8 = 21;
+const PROPERTY: u8 = 28;
+const PROPERTY_REPEAT: u8 = 29;
+
+/// Why an OASIS stream could not be read, or its polygons not fractured.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OasisErr {
+    BadMagic,
+    UnexpectedEof,
+    /// A record type outside the supported subset (e.g. PATH, CBLOCK).
+    UnsupportedRecord(u8),
+    /// A value the subset cannot represent, such as a placement angle that
+    /// is not a multiple of 90 degrees.
+    Unsupported(String),
+    /// A malformed value.
+    BadValue(String),
+    /// A modal variable used before it was set.
+    UndefinedModal(&'static str),
+    /// A cell reference number with no CELLNAME record.
+    UnknownCell(u64),
+    Shape(ShapeErr),
+    Decomp(DecompErr),
+}
+
+impl Display for OasisErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for OasisErr {}
+
+/// A layer and datatype pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Layer {
+    pub layer: u64,
+    pub datatype: u64,
+}
+
+/// An instance of another cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub cell: String,
+    pub transform: Transform,
+}
+
+/// The contents of one CELL record. Repetitions are expanded, so that each
+/// shape and placement appears once per position.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cell {
+    pub name: String,
+    /// Polygons as read, without a repeated closing point, in whatever
+    /// winding the file used.
+    pub polygons: Vec<(Layer, Vec<Point>)>,
+    pub rects: Vec<(Layer, Rect)>,
+    pub placements: Vec<Placement>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    /// Database units per micron.
+    pub unit: f64,
+    pub cells: Vec<Cell>,
+}
+
+impl Cell {
+    /// The rects of the decomposition of every polygon, on its layer.
+    fn fractured(&self) -> Result<Vec<(Layer, Rect)>, OasisErr> {
+        let mut rects = Vec::new();
+        for (layer, points) in &self.polygons {
+            let ring =
+                normalize_ring(points.clone()).map_err(OasisErr::Shape)?;
+            let decomposition =
+                Decomposer::decompose(ring).map_err(OasisErr::Decomp)?;
+            rects.extend(decomposition.into_iter().map(|rect| (*layer, rect)));
+        }
+        Ok(rects)
+    }
+
+    /// Replace every polygon by the rects of its decomposition, on the same
+    /// layer. If any polygon cannot be decomposed, the cell is left as it
+    /// was.
+    pub fn fracture(&mut self) -> Result<(), OasisErr> {
+        let rects = self.fractured()?;
+        self.polygons.clear();
+        self.rects.extend(rects);
+        Ok(())
+    }
+}
+
+impl Layout {
+    /// Fracture the polygons of every cell (see `Cell::fracture`). If any
+    /// polygon cannot be decomposed, no cell is changed.
+    pub fn fracture(&mut self) -> Result<(), OasisErr> {
+        let fractured = self
+            .cells
+            .iter()
+            .map(Cell::fractured)
+            .collect::<Result<Vec<_>, OasisErr>>()?;
+        for (cell, rects) in self.cells.iter_mut().zip(fractured) {
+            cell.polygons.clear();
+            cell.rects.extend(rects);
+        }
+        Ok(())
+    }
+}
+
+/// Map the rotation (in quarter turns counter-clockwise) and flip (about the
+/// x axis, before rotating) of a placement to an orientation.
+fn orientation(quarters: u64, flip: bool) -> Orientation {
+    match (quarters % 4, flip) {
+        (0, false) => Orientation::N,
+        (1, false) => Orientation::W,
+        (2, false) => Orientation::S,
+        (3, false) => Orientation::E,
+        (0, true) => Orientation::FS,
+        (1, true) => Orientation::FW,
+        (2, true) => Orientation::FN,
+        _ => Orientation::FE,
+    }
+}
+
+/// The inverse of `orientation`.
+fn quarters_and_flip(orientation: Orientation) -> (u64, bool) {
+    match orientation {
+        Orientation::N => (0, false),
+        Orientation::W => (1, false),
+        Orientation::S => (2, false),
+        Orientation::E => (3, false),
+        Orientation::FS => (0, true),
+        Orientation::FW => (1, true),
+        Orientation::FN => (2, true),
+        Orientation::FE => (3, true),
+    }
+}
+
+/// A cell, by name or by CELLNAME reference number, which may only be
+/// resolved once the whole stream has been read.
+#[derive(Clone, Debug)]
+enum CellRef {
+    Number(u64),
+    Name(String),
+}
+
+/// The modal variables of the subset, reset at each CELL record.
+#[derive(Clone, Debug, Default)]
+struct Modal {
+    relative: bool,
+    placement_x: isize,
+    placement_y: isize,
+    placement_cell: Option<CellRef>,
+    geometry_x: isize,
+    geometry_y: isize,
+    text_x: isize,
+    text_y: isize,
+    layer: Option<u64>,
+    datatype: Option<u64>,
+    textlayer: Option<u64>,
+    texttype: Option<u64>,
+    geometry_w: Option<isize>,
+    geometry_h: Option<isize>,
+    polygon_point_list: Option<Vec<Point>>,
+    repetition: Option<Vec<Point>>,
+    property_values: Option<()>,
+}
+
+impl Modal {
+    fn set<T: Clone>(
+        slot: &mut Option<T>,
+        given: Option<T>,
+        name: &'static str,
+    ) -> Result<T, OasisErr> {
+        if let Some(value) = given {
+            *slot = Some(value);
+        }
+        slot.clone().ok_or(OasisErr::UndefinedModal(name))
+    }
+
+    /// Update a modal coordinate with a value read in the current xy-mode.
+    fn coordinate(
+        relative: bool,
+        slot: &mut isize,
+        given: Option<isize>,
+    ) -> Result<(), OasisErr> {
+        match given {
+            Some(value) if relative => {
+                *slot = slot.checked_add(value).ok_or_else(overflow)?
+            }
+            Some(value) => *slot = value,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, OasisErr> {
+        let byte = *self.bytes.get(self.pos).ok_or(OasisErr::UnexpectedEof)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// The number of bytes left in the stream.
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], OasisErr> {
+        if len > self.remaining() {
+            return Err(OasisErr::UnexpectedEof);
+        }
+        let taken = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn uint(&mut self) -> Result<u64, OasisErr> {
+        let (mut value, mut shift) = (0_u64, 0);
+        loop {
+            let byte = self.byte()?;
+            if shift >= 64 || (shift > 57 && (byte & 0x7f) >> (64 - shift) != 0)
+            {
+                return Err(OasisErr::BadValue(
+                    "unsigned integer overflows 64 bits".to_string(),
+                ));
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    /// A signed integer keeps its sign in the lowest bit.
+    fn sint(&mut self) -> Result<isize, OasisErr> {
+        let value = self.uint()?;
+        let magnitude = (value >> 1) as isize;
+        Ok(if value & 1 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        })
+    }
+
+    fn length(&mut self) -> Result<isize, OasisErr> {
+        isize::try_from(self.uint()?).map_err(|_| overflow())
+    }
+
+    /// The number of items in a list whose items each take at least one
+    /// byte, so that the rest of the stream must hold that many bytes.
+    fn count(&mut self) -> Result<usize, OasisErr> {
+        usize::try_from(self.uint()?)
+            .ok()
+            .filter(|&count| count <= self.remaining())
+            .ok_or(OasisErr::UnexpectedEof)
+    }
+
+    /// The number of repetitions along one dimension of an array, which is
+    /// stored less `offset`.
+    fn dimension(&mut self, offset: u64) -> Result<u64, OasisErr> {
+        self.uint()?.checked_add(offset).ok_or_else(overflow)
+    }
+
+    fn real_of_type(&mut self, kind: u64) -> Result<f64, OasisErr> {
+        Ok(match kind {
+            0 => self.uint()? as f64,
+            1 => -(self.uint()? as f64),
+            2 => 1.0 / self.uint()? as f64,
+            3 => -1.0 / self.uint()? as f64,
+            4 => self.uint()? as f64 / self.uint()? as f64,
+            5 => -(self.uint()? as f64 / self.uint()? as f64),
+            6 => f32::from_le_bytes(self.take(4)?.try_into().unwrap()) as f64,
+            7 => f64::from_le_bytes(self.take(8)?.try_into().unwrap()),
+            _ => return Err(OasisErr::BadValue(format!("real type {}", kind))),
+        })
+    }
+
+    fn real(&mut self) -> Result<f64, OasisErr> {
+        let kind = self.uint()?;
+        self.real_of_type(kind)
+    }
+
+    fn bytes_string(&mut self) -> Result<&'a [u8], OasisErr> {
+        let len = self.count()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, OasisErr> {
+        String::from_utf8(self.bytes_string()?.to_vec())
+            .map_err(|err| OasisErr::BadValue(err.to_string()))
+    }
+
+    fn interval(&mut self) -> Result<(), OasisErr> {
+        match self.uint()? {
+            0 => {}
+            1..=3 => {
+                self.uint()?;
+            }
+            4 => {
+                self.uint()?;
+                self.uint()?;
+            }
+            kind => {
+                return Err(OasisErr::BadValue(format!(
+                    "interval type {}",
+                    kind
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// A direction in 2-delta (`bits == 2`) or 3-delta (`bits == 3`) form:
+    /// east, north, west, south, then the four diagonals.
+    fn delta(&mut self, bits: u32) -> Result<Point, OasisErr> {
+        let value = self.uint()?;
+        let direction = value & ((1 << bits) - 1);
+        Ok(octangular(direction, (value >> bits) as isize))
+    }
+
+    fn g_delta(&mut self) -> Result<Point, OasisErr> {
+        let value = self.uint()?;
+        if value & 1 == 0 {
+            Ok(octangular((value >> 1) & 7, (value >> 4) as isize))
+        } else {
+            let x = (value >> 2) as isize;
+            let x = if value & 2 == 2 { -x } else { x };
+            Ok(Point::new(x, self.sint()?))
+        }
+    }
+
+    /// The vertices of a point list, relative to the first (implicit) one,
+    /// which is left out. For polygons, 1-delta lists omit the last vertex,
+    /// which is implied by the alternation of horizontal and vertical edges.
+    fn point_list(&mut self) -> Result<Vec<Point>, OasisErr> {
+        let kind = self.uint()?;
+        let count = self.count()?;
+        let mut points = Vec::with_capacity(count + 1);
+        let mut at = Point::default();
+        let mut step = Point::default();
+        for ix in 0..count {
+            let delta = match kind {
+                0 | 1 => {
+                    let d = self.sint()?;
+                    if ix.is_multiple_of(2) == (kind == 0) {
+                        Point::new(d, 0)
+                    } else {
+                        Point::new(0, d)
+                    }
+                }
+                2 => self.delta(2)?,
+                3 => self.delta(3)?,
+                4 => self.g_delta()?,
+                5 => {
+                    step = shifted(step, self.g_delta()?)?;
+                    step
+                }
+                _ => {
+                    return Err(OasisErr::BadValue(format!(
+                        "point list type {}",
+                        kind
+                    )))
+                }
+            };
+            at = shifted(at, delta)?;
+            points.push(at);
+        }
+        if kind <= 1 {
+            // Close the alternation: the implied vertex shares the start's
+            // coordinate along the direction of the first edge.
+            let implied = if count.is_multiple_of(2) == (kind == 0) {
+                Point::new(0, at.y)
+            } else {
+                Point::new(at.x, 0)
+            };
+            points.push(implied);
+        }
+        Ok(points)
+    }
+
+    /// The offsets at which a repetition places its element, including the
+    /// element's own position at the origin.
+    fn repetition(
+        &mut self,
+        modal: &mut Option<Vec<Point>>,
+    ) -> Result<Vec<Point>, OasisErr> {
+        let kind = self.uint()?;
+        let grid_of = |reader: &mut Self, gridded: bool| {
+            if gridded {
+                reader.length()
+            } else {
+                Ok(1)
+            }
+        };
+        let offsets = match kind {
+            0 => {
+                return modal
+                    .clone()
+                    .ok_or(OasisErr::UndefinedModal("repetition"))
+            }
+            1 => {
+                let (nx, ny) = (self.dimension(2)?, self.dimension(2)?);
+                let (dx, dy) = (self.length()?, self.length()?);
+                grid(
+                    nx,
+                    Point::new(dx, 0),
+                    ny,
+                    Point::new(0, dy),
+                    self.remaining(),
+                )?
+            }
+            2 => {
+                let n = self.dimension(2)?;
+                let dx = self.length()?;
+                grid(
+                    n,
+                    Point::new(dx, 0),
+                    1,
+                    Point::default(),
+                    self.remaining(),
+                )?
+            }
+            3 => {
+                let n = self.dimension(2)?;
+                let dy = self.length()?;
+                grid(
+                    1,
+                    Point::default(),
+                    n,
+                    Point::new(0, dy),
+                    self.remaining(),
+                )?
+            }
+            4..=7 => {
+                let n = self.count()?;
+                let g = grid_of(self, kind == 5 || kind == 7)?;
+                let mut at: isize = 0;
+                let mut offsets = Vec::with_capacity(n + 2);
+                offsets.push(Point::default());
+                for _ in 0..=n {
+                    at = self
+                        .length()?
+                        .checked_mul(g)
+                        .and_then(|space| at.checked_add(space))
+                        .ok_or_else(overflow)?;
+                    offsets.push(if kind <= 5 {
+                        Point::new(at, 0)
+                    } else {
+                        Point::new(0, at)
+                    });
+                }
+                offsets
+            }
+            8 => {
+                let (n, m) = (self.dimension(2)?, self.dimension(2)?);
+                let (dn, dm) = (self.g_delta()?, self.g_delta()?);
+                grid(n, dn, m, dm, self.remaining())?
+            }
+            9 => {
+                let n = self.dimension(2)?;
+                let dn = self.g_delta()?;
+                grid(n, dn, 1, Point::default(), self.remaining())?
+            }
+            10 | 11 => {
+                let n = self.count()?;
+                let g = grid_of(self, kind == 11)?;
+                let mut at = Point::default();
+                let mut offsets = Vec::with_capacity(n + 2);
+                offsets.push(at);
+                for _ in 0..=n {
+                    let d = self.g_delta()?;
+                    let space = match (d.x.checked_mul(g), d.y.checked_mul(g)) {
+                        (Some(x), Some(y)) => Point::new(x, y),
+                        _ => return Err(overflow()),
+                    };
+                    at = shifted(at, space)?;
+                    offsets.push(at);
+                }
+                offsets
+            }
+            _ => {
+                return Err(OasisErr::BadValue(format!(
+                    "repetition type {}",
+                    kind
+                )))
+            }
+        };
+        *modal = Some(offsets.clone());
+        Ok(offsets)
+    }
+
+    /// Read (and drop) the values of a PROPERTY record.
+    fn property(&mut self, modal: &mut Modal) -> Result<(), OasisErr> {
+        let info = self.byte()?;
+        if info & 0x04 != 0 {
+            if info & 0x02 != 0 {
+                self.uint()?;
+            } else {
+                self.bytes_string()?;
+            }
+        }
+        if info & 0x08 != 0 {
+            return Modal::set(&mut modal.property_values, None, "property")
+                .map(|_| ());
+        }
+        let count = match info >> 4 {
+            15 => self.uint()?,
+            count => u64::from(count),
+        };
+        for _ in 0..count {
+            match self.uint()? {
+                kind @ 0..=7 => {
+                    self.real_of_type(kind)?;
+                }
+                8 | 9 | 13..=15 => {
+                    self.uint()?;
+                }
+                10..=12 => {
+                    self.bytes_string()?;
+                }
+                kind => {
+                    return Err(OasisErr::BadValue(format!(
+                        "property value type {}",
+                        kind
+                    )))
+                }
+            }
+        }
+        modal.property_values = Some(());
+        Ok(())
+    }
+}
+
+fn octangular(direction: u64, magnitude: isize) -> Point {
+    let (dx, dy) = match direction {
+        0 => (1, 0),
+        1 => (0, 1),
+        2 => (-1, 0),
+        3 => (0, -1),
+        4 => (1, 1),
+        5 => (-1, 1),
+        6 => (-1, -1),
+        _ => (1, -1),
+    };
+    Point::new(dx * magnitude, dy * magnitude)
+}
+
+fn overflow() -> OasisErr {
+    OasisErr::BadValue("value out of range".to_string())
+}
+
+/// `at` moved by `by`, unless that overflows.
+fn shifted(at: Point, by: Point) -> Result<Point, OasisErr> {
+    match (at.x.checked_add(by.x), at.y.checked_add(by.y)) {
+        (Some(x), Some(y)) => Ok(Point::new(x, y)),
+        _ => Err(overflow()),
+    }
+}
+
+/// The offsets of an `n` by `m` array. The array takes a few bytes of the
+/// stream whatever its size, so no more offsets are allocated up front than
+/// the `remaining` bytes of the stream could hold points.
+fn grid(
+    n: u64,
+    step_n: Point,
+    m: u64,
+    step_m: Point,
+    remaining: usize,
+) -> Result<Vec<Point>, OasisErr> {
+    let count = n.checked_mul(m).ok_or_else(overflow)?;
+    let mut offsets = Vec::with_capacity(
+        usize::try_from(count).unwrap_or(usize::MAX).min(remaining),
+    );
+    let mut row = Point::default();
+    for j in 0..m {
+        if j > 0 {
+            row = shifted(row, step_m)?;
+        }
+        let mut at = row;
+        for i in 0..n {
+            if i > 0 {
+                at = shifted(at, step_n)?;
+            }
+            offsets.push(at);
+        }
+    }
+    Ok(offsets)
+}
+
+#[inline]
+fn bit(info: u8, mask: u8) -> bool {
+    info & mask != 0
+}
+
+/// Read an uncompressed OASIS stream. Only CELL, PLACEMENT, RECTANGLE and
+/// POLYGON records contribute to the layout; name tables, TEXT and PROPERTY
+/// records are read past, and any other record is reported as unsupported.
+pub fn read(bytes: &[u8]) -> Result<Layout, OasisErr> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(OasisErr::BadMagic);
+    }
+    let mut reader = Reader {
+        bytes,
+        pos: MAGIC.len(),
+    };
+    if reader.byte()? != START {
+        return Err(OasisErr::BadValue("expected START".to_string()));
+    }
+    reader.string()?;
+    let unit = reader.real()?;
+    if reader.uint()? == 0 {
+        for _ in 0..12 {
+            reader.uint()?;
+        }
+    }
+
+    let mut cell_names = HashMap::new();
+    let mut implicit_names = 0;
+    let mut cells: Vec<(CellRef, Cell)> = Vec::new();
+    let mut placements: Vec<(usize, CellRef, Transform)> = Vec::new();
+    let mut modal = Modal::default();
+    loop {
+        let record = reader.byte()?;
+        let in_cell = |cells: &[(CellRef, Cell)]| {
+            cells.len().checked_sub(1).ok_or(OasisErr::BadValue(format!(
+                "record {} outside of a cell",
+                record
+            )))
+        };
+        match record {
+            PAD => {}
+            END => break,
+            CELLNAME_IMPLICIT | CELLNAME => {
+                let name = reader.string()?;
+                let number = if record == CELLNAME {
+                    reader.uint()?
+                } else {
+                    implicit_names += 1;
+                    implicit_names - 1
+                };
+                cell_names.insert(number, name);
+            }
+            TEXTSTRING_IMPLICIT | PROPNAME_IMPLICIT | PROPSTRING_IMPLICIT => {
+                reader.bytes_string()?;
+            }
+            TEXTSTRING | PROPNAME | PROPSTRING => {
+                reader.bytes_string()?;
+                reader.uint()?;
+            }
+            LAYERNAME | LAYERNAME_TEXT => {
+                reader.bytes_string()?;
+                reader.interval()?;
+                reader.interval()?;
+            }
+            CELL_REF | CELL_NAME => {
+                let cell = if record == CELL_REF {
+                    CellRef::Number(reader.uint()?)
+                } else {
+                    CellRef::Name(reader.string()?)
+                };
+                cells.push((cell, Cell::default()));
+                modal = Modal::default();
+            }
+            XYABSOLUTE => modal.relative = false,
+            XYRELATIVE => modal.relative = true,
+            PLACEMENT | PLACEMENT_MAG_ANGLE => {
+                let ix = in_cell(&cells)?;
+                let info = reader.byte()?;
+                let cell = if bit(info, 0x80) {
+                    Some(if bit(info, 0x40) {
+                        CellRef::Number(reader.uint()?)
+                    } else {
+                        CellRef::Name(reader.string()?)
+                    })
+                } else {
+                    None
+                };
+                let cell = Modal::set(
+                    &mut modal.placement_cell,
+                    cell,
+                    "placement-cell",
+                )?;
+                let (scale, quarters) = if record == PLACEMENT {
+                    (1, u64::from((info >> 1) & 3))
+                } else {
+                    let magnification =
+                        if bit(info, 0x04) { reader.real()? } else { 1.0 };
+                    let angle =
+                        if bit(info, 0x02) { reader.real()? } else { 0.0 };
+                    if magnification.fract() != 0.0 || angle % 90.0 != 0.0 {
+                        return Err(OasisErr::Unsupported(format!(
+                            "magnification {} at angle {}",
+                            magnification, angle
+                        )));
+                    }
+                    (
+                        magnification as isize,
+                        (angle / 90.0).rem_euclid(4.0) as u64,
+                    )
+                };
+                let x = bit(info, 0x20).then(|| reader.sint()).transpose()?;
+                let y = bit(info, 0x10).then(|| reader.sint()).transpose()?;
+                Modal::coordinate(modal.relative, &mut modal.placement_x, x)?;
+                Modal::coordinate(modal.relative, &mut modal.placement_y, y)?;
+                let offsets = if bit(info, 0x08) {
+                    reader.repetition(&mut modal.repetition)?
+                } else {
+                    vec![Point::default()]
+                };
+                for offset in offsets {
+                    let at = shifted(
+                        Point::new(modal.placement_x, modal.placement_y),
+                        offset,
+                    )?;
+                    let transform = Transform::new(
+                        orientation(quarters, bit(info, 0x01)),
+                        at,
+                    )
+                    .with_scale(scale);
+                    placements.push((ix, cell.clone(), transform));
+                }
+            }
+            TEXT => {
+                in_cell(&cells)?;
+                let info = reader.byte()?;
+                if bit(info, 0x40) {
+                    if bit(info, 0x20) {
+                        reader.uint()?;
+                    } else {
+                        reader.bytes_string()?;
+                    }
+                }
+                let textlayer =
+                    bit(info, 0x01).then(|| reader.uint()).transpose()?;
+                Modal::set(&mut modal.textlayer, textlayer, "textlayer")?;
+                let texttype =
+                    bit(info, 0x02).then(|| reader.uint()).transpose()?;
+                Modal::set(&mut modal.texttype, texttype, "texttype")?;
+                let x = bit(info, 0x10).then(|| reader.sint()).transpose()?;
+                let y = bit(info, 0x08).then(|| reader.sint()).transpose()?;
+                Modal::coordinate(modal.relative, &mut modal.text_x, x)?;
+                Modal::coordinate(modal.relative, &mut modal.text_y, y)?;
+                if bit(info, 0x04) {
+                    reader.repetition(&mut modal.repetition)?;
+                }
+            }
+            RECTANGLE | POLYGON => {
+                let ix = in_cell(&cells)?;
+                let info = reader.byte()?;
+                let layer =
+                    bit(info, 0x01).then(|| reader.uint()).transpose()?;
+                let datatype =
+                    bit(info, 0x02).then(|| reader.uint()).transpose()?;
+                let layer = Layer {
+                    layer: Modal::set(&mut modal.layer, layer, "layer")?,
+                    datatype: Modal::set(
+                        &mut modal.datatype,
+                        datatype,
+                        "datatype",
+                    )?,
+                };
+                let shape = if record == RECTANGLE {
+                    let square = bit(info, 0x80);
+                    let w =
+                        bit(info, 0x40).then(|| reader.length()).transpose()?;
+                    let w = Modal::set(&mut modal.geometry_w, w, "geometry-w")?;
+                    let h = if square {
+                        Some(w)
+                    } else {
+                        bit(info, 0x20).then(|| reader.length()).transpose()?
+                    };
+                    let h = Modal::set(&mut modal.geometry_h, h, "geometry-h")?;
+                    Ok(Point::new(w, h))
+                } else {
+                    let points = bit(info, 0x20)
+                        .then(|| reader.point_list())
+                        .transpose()?;
+                    Err(Modal::set(
+                        &mut modal.polygon_point_list,
+                        points,
+                        "polygon-point-list",
+                    )?)
+                };
+                let x = bit(info, 0x10).then(|| reader.sint()).transpose()?;
+                let y = bit(info, 0x08).then(|| reader.sint()).transpose()?;
+                Modal::coordinate(modal.relative, &mut modal.geometry_x, x)?;
+                Modal::coordinate(modal.relative, &mut modal.geometry_y, y)?;
+                let offsets = if bit(info, 0x04) {
+                    reader.repetition(&mut modal.repetition)?
+                } else {
+                    vec![Point::default()]
+                };
+
+                let cell = &mut cells[ix].1;
+                for offset in offsets {
+                    let at = shifted(
+                        Point::new(modal.geometry_x, modal.geometry_y),
+                        offset,
+                    )?;
+                    match &shape {
+                        Ok(size) => cell
+                            .rects
+                            .push((layer, Rect::new(at, shifted(at, *size)?))),
+                        Err(deltas) => cell.polygons.push((
+                            layer,
+                            std::iter::once(Ok(at))
+                                .chain(deltas.iter().map(|d| shifted(at, *d)))
+                                .collect::<Result<Vec<Point>, OasisErr>>()?,
+                        )),
+                    }
+                }
+            }
+            PROPERTY => reader.property(&mut modal)?,
+            PROPERTY_REPEAT => {}
+            _ => return Err(OasisErr::UnsupportedRecord(record)),
+        }
+    }
+
+    let resolve = |cell: CellRef| match cell {
+        CellRef::Name(name) => Ok(name),
+        CellRef::Number(number) => cell_names
+            .get(&number)
+            .cloned()
+            .ok_or(OasisErr::UnknownCell(number)),
+    };
+    for (ix, cell, transform) in placements {
+        let cell = resolve(cell)?;
+        cells[ix].1.placements.push(Placement { cell, transform });
+    }
+    let cells = cells
+        .into_iter()
+        .map(|(name, cell)| {
+            Ok(Cell {
+                name: resolve(name)?,
+                ..cell
+            })
+        })
+        .collect::<Result<Vec<Cell>, OasisErr>>()?;
+    Ok(Layout { unit, cells })
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn uint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    fn sint(&mut self, value: isize) {
+        self.uint((value.unsigned_abs() as u64) << 1 | u64::from(value < 0));
+    }
+
+    fn real(&mut self, value: f64) {
+        if value.fract() == 0.0 && value.abs() < u64::MAX as f64 {
+            self.uint(u64::from(value < 0.0));
+            self.uint(value.abs() as u64);
+        } else {
+            self.uint(7);
+            self.bytes.extend(value.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, value: &[u8]) {
+        self.uint(value.len() as u64);
+        self.bytes.extend(value);
+    }
+
+    /// A POLYGON point list: 2-delta if every edge is horizontal or
+    /// vertical, g-delta otherwise.
+    fn point_list(&mut self, points: &[Point]) {
+        let deltas = points
+            .windows(2)
+            .map(|pair| {
+                Point::new(pair[1].x - pair[0].x, pair[1].y - pair[0].y)
+            })
+            .collect::<Vec<Point>>();
+        let manhattan = deltas.iter().all(|d| d.x == 0 || d.y == 0);
+        self.uint(if manhattan { 2 } else { 4 });
+        self.uint(deltas.len() as u64);
+        for d in deltas {
+            if manhattan {
+                let (direction, magnitude) = match (d.x.signum(), d.y.signum())
+                {
+                    (1, _) => (0, d.x),
+                    (_, 1) => (1, d.y),
+                    (-1, _) => (2, -d.x),
+                    _ => (3, -d.y),
+                };
+                self.uint((magnitude as u64) << 2 | direction);
+            } else {
+                self.uint(
+                    (d.x.unsigned_abs() as u64) << 2
+                        | u64::from(d.x < 0) << 1
+                        | 1,
+                );
+                self.sint(d.y);
+            }
+        }
+    }
+}
+
+/// Write a layout as an uncompressed OASIS stream: one CELLNAME record per
+/// cell name, then each cell with its placements, RECTANGLE and POLYGON
+/// records. Coordinates are absolute and no modal variable is relied upon.
+pub fn write(layout: &Layout) -> Vec<u8> {
+    let mut names = HashMap::new();
+    let mut order = Vec::new();
+    let referenced = layout.cells.iter().map(|cell| &cell.name).chain(
+        layout.cells.iter().flat_map(|cell| {
+            cell.placements.iter().map(|placement| &placement.cell)
+        }),
+    );
+    for name in referenced {
+        if !names.contains_key(name) {
+            names.insert(name, order.len() as u64);
+            order.push(name);
+        }
+    }
+
+    let mut w = Writer::default();
+    w.bytes.extend(MAGIC);
+    w.uint(START.into());
+    w.string(b"1.0");
+    w.real(layout.unit);
+    // Table offsets are stored here, all zero: there are no strict tables.
+    w.uint(0);
+    for _ in 0..12 {
+        w.uint(0);
+    }
+    for name in &order {
+        w.uint(CELLNAME_IMPLICIT.into());
+        w.string(name.as_bytes());
+    }
+
+    for cell in &layout.cells {
+        w.uint(CELL_REF.into());
+        w.uint(names[&cell.name]);
+        for placement in &cell.placements {
+            let (quarters, flip) =
+                quarters_and_flip(placement.transform.orientation);
+            let offset = placement.transform.offset;
+            if placement.transform.scale == 1 {
+                w.uint(PLACEMENT.into());
+                w.bytes.push(0xf0 | (quarters as u8) << 1 | u8::from(flip));
+                w.uint(names[&placement.cell]);
+            } else {
+                w.uint(PLACEMENT_MAG_ANGLE.into());
+                w.bytes.push(0xf6 | u8::from(flip));
+                w.uint(names[&placement.cell]);
+                w.real(placement.transform.scale as f64);
+                w.real(quarters as f64 * 90.0);
+            }
+            w.sint(offset.x);
+            w.sint(offset.y);
+        }
+        for (layer, rect) in &cell.rects {
+            w.uint(RECTANGLE.into());
+            w.bytes.push(0x7b);
+            w.uint(layer.layer);
+            w.uint(layer.datatype);
+            w.uint(rect.width() as u64);
+            w.uint(rect.height() as u64);
+            w.sint(rect.x_min());
+            w.sint(rect.y_min());
+        }
+        for (layer, points) in &cell.polygons {
+            w.uint(POLYGON.into());
+            w.bytes.push(0x3b);
+            w.uint(layer.layer);
+            w.uint(layer.datatype);
+            w.point_list(points);
+            w.sint(points.first().map_or(0, |p| p.x));
+            w.sint(points.first().map_or(0, |p| p.y));
+        }
+    }
+
+    // The END record pads the stream so that it is exactly 256 bytes long,
+    // and is not validated.
+    w.uint(END.into());
+    w.string(&[0; 252]);
+    w.uint(0);
+    w.bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METAL: Layer = Layer {
+        layer: 1,
+        datatype: 0,
+    };
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    fn layout() -> Layout {
+        let placements = [
+            Orientation::N,
+            Orientation::S,
+            Orientation::E,
+            Orientation::W,
+            Orientation::FN,
+            Orientation::FS,
+            Orientation::FE,
+            Orientation::FW,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(ix, orientation)| Placement {
+            cell: "leaf".to_string(),
+            transform: Transform::new(
+                orientation,
+                Point::new(100 * ix as isize, -50),
+            )
+            .with_scale(if ix % 2 == 0 { 1 } else { 3 }),
+        })
+        .collect();
+        Layout {
+            unit: 1000.0,
+            cells: vec![
+                Cell {
+                    name: "top".to_string(),
+                    placements,
+                    ..Default::default()
+                },
+                Cell {
+                    name: "leaf".to_string(),
+                    polygons: vec![(
+                        METAL,
+                        ring(&[
+                            (-4, -4),
+                            (-4, 0),
+                            (-2, 0),
+                            (-2, -2),
+                            (0, -2),
+                            (0, -4),
+                        ]),
+                    )],
+                    rects: vec![
+                        (METAL, Rect::from_bounds(-10, -20, -5, 7)),
+                        (
+                            Layer {
+                                layer: 300,
+                                datatype: 2,
+                            },
+                            Rect::from_bounds(0, 0, 1, 1),
+                        ),
+                    ],
+                    placements: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    /// The start of a stream, up to its first CELL record.
+    fn header() -> Writer {
+        let mut w = Writer::default();
+        w.bytes.extend(MAGIC);
+        w.uint(START.into());
+        w.string(b"1.0");
+        w.real(1000.0);
+        w.uint(1);
+        w.uint(CELL_NAME.into());
+        w.string(b"top");
+        w
+    }
+
+    fn finish(mut w: Writer) -> Vec<u8> {
+        w.uint(END.into());
+        w.bytes
+    }
+
+    #[test]
+    fn layout_round_trips() {
+        let layout = layout();
+        assert_eq!(read(&write(&layout)), Ok(layout));
+    }
+
+    #[test]
+    fn repetitions_are_expanded() {
+        let mut w = header();
+        // A 3 by 2 array of 1 by 2 rects, 10 apart along x and 20 along y.
+        w.uint(RECTANGLE.into());
+        w.bytes.push(0x7f);
+        w.uint(1);
+        w.uint(0);
+        w.uint(1);
+        w.uint(2);
+        w.sint(-5);
+        w.sint(-5);
+        w.uint(1);
+        w.uint(1);
+        w.uint(0);
+        w.uint(10);
+        w.uint(20);
+        // The same rect elsewhere, reusing the modal repetition.
+        w.uint(RECTANGLE.into());
+        w.bytes.push(0x1c);
+        w.sint(100);
+        w.sint(0);
+        w.uint(0);
+        // Two placements of "leaf", 7 apart along y.
+        w.uint(PLACEMENT.into());
+        w.bytes.push(0xb8);
+        w.string(b"leaf");
+        w.sint(0);
+        w.sint(-3);
+        w.uint(3);
+        w.uint(0);
+        w.uint(7);
+        let layout = read(&finish(w)).unwrap();
+        let rects = &layout.cells[0].rects;
+        assert_eq!(rects.len(), 12);
+        assert_eq!(rects[0], (METAL, Rect::from_bounds(-5, -5, -4, -3)));
+        assert_eq!(rects[5], (METAL, Rect::from_bounds(15, 15, 16, 17)));
+        assert_eq!(rects[11], (METAL, Rect::from_bounds(120, 20, 121, 22)));
+        let offsets = layout.cells[0]
+            .placements
+            .iter()
+            .map(|placement| placement.transform.offset)
+            .collect::<Vec<Point>>();
+        assert_eq!(offsets, ring(&[(0, -3), (0, 4)]));
+    }
+
+    #[test]
+    fn malformed_counts_are_rejected() {
+        // Any cut before the END record, which is 256 bytes long.
+        let bytes = write(&layout());
+        for len in 0..=bytes.len() - 256 {
+            assert!(read(&bytes[..len]).is_err());
+        }
+
+        let mut w = Writer::default();
+        w.bytes.extend(MAGIC);
+        w.uint(START.into());
+        w.uint(u64::MAX);
+        assert_eq!(read(&w.bytes), Err(OasisErr::UnexpectedEof));
+
+        for count in [u64::MAX, 1000] {
+            let mut w = header();
+            w.uint(POLYGON.into());
+            w.bytes.push(0x23);
+            w.uint(1);
+            w.uint(0);
+            w.uint(0);
+            w.uint(count);
+            assert_eq!(read(&finish(w)), Err(OasisErr::UnexpectedEof));
+        }
+
+        // A 1 by 2 rect with the given repetition.
+        let repeated = |repetition: &[u64]| {
+            let mut w = header();
+            w.uint(RECTANGLE.into());
+            w.bytes.push(0x67);
+            w.uint(1);
+            w.uint(0);
+            w.uint(1);
+            w.uint(2);
+            for &value in repetition {
+                w.uint(value);
+            }
+            read(&finish(w))
+        };
+        let overflows = Err(overflow());
+        assert_eq!(repeated(&[1, u64::MAX, 0, 1, 1]), overflows);
+        assert_eq!(repeated(&[8, 1 << 32, 1 << 32, 0, 0]), overflows);
+        assert_eq!(repeated(&[4, u64::MAX, 1]), Err(OasisErr::UnexpectedEof));
+        assert_eq!(repeated(&[2, 1, u64::MAX]), overflows);
+        assert_eq!(repeated(&[2, 1, 1 << 62]), overflows);
+        assert_eq!(repeated(&[4, 1, 5, 7]).unwrap().cells[0].rects.len(), 3);
+    }
+
+    #[test]
+    fn modal_repetition_must_be_set() {
+        let mut w = header();
+        w.uint(RECTANGLE.into());
+        w.bytes.push(0xff);
+        w.uint(1);
+        w.uint(0);
+        w.uint(1);
+        w.sint(0);
+        w.sint(0);
+        w.uint(0);
+        assert_eq!(
+            read(&finish(w)),
+            Err(OasisErr::UndefinedModal("repetition"))
+        );
+    }
+
+    #[test]
+    fn fracture_replaces_polygons() {
+        let mut layout = layout();
+        layout.fracture().unwrap();
+        let leaf = &layout.cells[1];
+        assert!(leaf.polygons.is_empty());
+        assert_eq!(leaf.rects.len(), 4);
+        assert_eq!(
+            leaf.rects[2..]
+                .iter()
+                .map(|(_, rect)| rect.area())
+                .sum::<i128>(),
+            12
+        );
+    }
+
+    #[test]
+    fn failed_fracture_keeps_the_layout() {
+        let mut layout = layout();
+        layout.cells[0]
+            .polygons
+            .push((METAL, ring(&[(0, 0), (0, 2), (2, 2), (2, 0)])));
+        layout.cells[1]
+            .polygons
+            .push((METAL, ring(&[(0, 0), (0, 2), (2, 0)])));
+        let before = layout.clone();
+        assert!(matches!(
+            layout.fracture(),
+            Err(OasisErr::Shape(ShapeErr::NotRectilinear(..)))
+        ));
+        assert_eq!(layout, before);
+        assert!(layout.cells[1].fracture().is_err());
+        assert_eq!(layout, before);
+    }
+}
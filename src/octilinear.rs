@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    polygon::{Polygon, Winding},
+    rect::Rect,
+};
+
+/// A trapezoid with horizontal bases, whose sides are each vertical or at
+/// 45 degrees. A base of zero width makes it a triangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trapezoid {
+    pub y_min: isize,
+    pub y_max: isize,
+    /// The x bounds of the base at `y_min`.
+    pub bottom: (isize, isize),
+    /// The x bounds of the base at `y_max`.
+    pub top: (isize, isize),
+}
+
+impl Trapezoid {
+    #[inline]
+    pub fn height(&self) -> isize {
+        self.y_max - self.y_min
+    }
+
+    /// Twice the area, which is a half-integer for triangles of odd height.
+    pub fn area2(&self) -> i128 {
+        let widths = (self.bottom.1 - self.bottom.0) as i128
+            + (self.top.1 - self.top.0) as i128;
+        widths * self.height() as i128
+    }
+
+    #[inline]
+    pub fn is_triangle(&self) -> bool {
+        self.bottom.0 == self.bottom.1 || self.top.0 == self.top.1
+    }
+
+    /// The vertices, clockwise from the lower left one, without the
+    /// duplicate vertex of a triangle.
+    pub fn points(&self) -> Vec<Point> {
+        let mut points = vec![
+            Point::new(self.bottom.0, self.y_min),
+            Point::new(self.top.0, self.y_max),
+            Point::new(self.top.1, self.y_max),
+            Point::new(self.bottom.1, self.y_min),
+        ];
+        points.dedup();
+        if self.bottom.0 == self.bottom.1 {
+            points.pop();
+        }
+        points
+    }
+
+    #[inline]
+    pub fn bbox(&self) -> Rect {
+        Rect::bounding(&self.points())
+    }
+}
+
+/// A piece of the decomposition of an octilinear polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece {
+    Rect(Rect),
+    /// A right-isosceles triangle, with one vertical leg and one horizontal
+    /// (its base of zero width being the other end of the vertical leg).
+    Trapezoid(Trapezoid),
+}
+
+impl Piece {
+    /// Twice the area of the piece.
+    pub fn area2(&self) -> i128 {
+        match self {
+            Piece::Rect(rect) => 2 * rect.area(),
+            Piece::Trapezoid(trapezoid) => trapezoid.area2(),
+        }
+    }
+
+    pub fn bbox(&self) -> Rect {
+        match self {
+            Piece::Rect(rect) => *rect,
+            Piece::Trapezoid(trapezoid) => trapezoid.bbox(),
+        }
+    }
+}
+
+/// A non-horizontal edge, from its lower end `lo` to its upper end `hi`.
+#[derive(Clone, Copy, Debug)]
+struct Slant {
+    lo: Point,
+    hi: Point,
+    /// -1, 0 or 1: how much x changes as y goes up by 1.
+    slope: isize,
+}
+
+impl Slant {
+    #[inline]
+    fn x_at(&self, y: isize) -> isize {
+        self.lo.x + self.slope * (y - self.lo.y)
+    }
+}
+
+/// Split the part of the polygon between the edges `left` and `right` and
+/// the scanlines `y_min` and `y_max` into a rect between the innermost
+/// points of its sides, and a right-isosceles triangle on each slanted side.
+///
+/// A parallelogram narrower than it is tall has no such rect. It is first
+/// cut into bands as tall as it is wide, which each split into two
+/// triangles, and a shorter band at the top.
+fn split(
+    left: &Slant,
+    right: &Slant,
+    y_min: isize,
+    y_max: isize,
+) -> Vec<Piece> {
+    let (lb, lt) = (left.x_at(y_min), left.x_at(y_max));
+    let (rb, rt) = (right.x_at(y_min), right.x_at(y_max));
+    let (x0, x1) = (lb.max(lt), rb.min(rt));
+    if x0 > x1 {
+        let width = rb - lb;
+        if width <= 0 {
+            return Vec::new();
+        }
+        return (y_min..y_max)
+            .step_by(width as usize)
+            .flat_map(|y| split(left, right, y, (y + width).min(y_max)))
+            .collect();
+    }
+    let mut pieces = Vec::with_capacity(3);
+    if lb != lt {
+        pieces.push(Piece::Trapezoid(Trapezoid {
+            y_min,
+            y_max,
+            bottom: (lb, x0),
+            top: (lt, x0),
+        }));
+    }
+    if x0 < x1 {
+        pieces.push(Piece::Rect(Rect::from_bounds(x0, y_min, x1, y_max)));
+    }
+    if rb != rt {
+        pieces.push(Piece::Trapezoid(Trapezoid {
+            y_min,
+            y_max,
+            bottom: (x1, rb),
+            top: (x1, rt),
+        }));
+    }
+    pieces
+}
+
+/// Sweep the scanlines through the vertices of `polygon` bottom up. Between
+/// two scanlines, the polygon is a row of trapezoids, each between a pair of
+/// consecutive edges crossing the band (by even-odd). A trapezoid is
+/// extended upwards for as long as the same pair of edges bounds a
+/// trapezoid in the bands above, and then split.
+fn sweep(polygon: &Polygon) -> Vec<Piece> {
+    let points = polygon.points();
+    let mut slants = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .filter(|(p, q)| p.y != q.y)
+        .map(|(&p, &q)| {
+            let (lo, hi) = if p.y < q.y { (p, q) } else { (q, p) };
+            Slant {
+                lo,
+                hi,
+                slope: (hi.x - lo.x).signum(),
+            }
+        })
+        .collect::<Vec<Slant>>();
+    slants.sort_unstable_by_key(|slant| slant.lo.y);
+
+    let mut ys = points.iter().map(|p| p.y).collect::<Vec<isize>>();
+    ys.sort_unstable();
+    ys.dedup();
+
+    let mut pieces = Vec::new();
+    // Indices into `slants` of the edges crossing the current band.
+    let mut active = Vec::<usize>::new();
+    let mut next = 0;
+    // The bottom of the open trapezoid between each pair of edges.
+    let mut open = BTreeMap::<(usize, usize), isize>::new();
+    for band in ys.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        active.retain(|&ix| slants[ix].hi.y > y0);
+        while next < slants.len() && slants[next].lo.y <= y0 {
+            active.push(next);
+            next += 1;
+        }
+        active.sort_unstable_by_key(|&ix| {
+            slants[ix].x_at(y0) + slants[ix].x_at(y1)
+        });
+
+        let pairs = active
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<(usize, usize)>>();
+        open.retain(|&(left, right), &mut y_min| {
+            let kept = pairs.contains(&(left, right));
+            if !kept {
+                pieces.extend(split(&slants[left], &slants[right], y_min, y0));
+            }
+            kept
+        });
+        for pair in pairs {
+            open.entry(pair).or_insert(y0);
+        }
+    }
+    let y_max = ys.last().copied().unwrap_or_default();
+    for ((left, right), y_min) in open {
+        pieces.extend(split(&slants[left], &slants[right], y_min, y_max));
+    }
+    pieces
+}
+
+impl Decomposer {
+    /// Decompose a polygon whose edges are horizontal, vertical or at 45
+    /// degrees into rects and right-isosceles triangles. The polygon may be
+    /// given in either orientation: it is made clockwise first.
+    ///
+    /// Rectilinear polygons are decomposed by `decompose`. Other polygons
+    /// cannot be: a `Geometry` keeps only the vertical edges of a polygon,
+    /// and its scanline cuts rects between them, so a slanted edge has
+    /// nowhere to go and no rect could fill the region beside it. They are
+    /// instead cut into horizontal bands at each vertex, and each band into
+    /// trapezoids between the edges crossing it.
+    pub fn decompose_octilinear(
+        polygon: impl Into<Polygon>,
+    ) -> Result<Vec<Piece>, DecompErr> {
+        let mut polygon = polygon.into();
+        if polygon.vertex_count() < 3 {
+            return Err(DecompErr::NotEnoughPoints);
+        } else if !polygon.is_octilinear() {
+            return Err(DecompErr::NotOctilinear);
+        }
+        if polygon.orientation() == Some(Winding::CounterClockwise) {
+            let mut points = polygon.into_points();
+            points.reverse();
+            polygon = Polygon::new(points);
+        }
+        if polygon.is_rectilinear() {
+            Ok(Decomposer::decompose(polygon)?
+                .into_iter()
+                .map(Piece::Rect)
+                .collect())
+        } else {
+            Ok(sweep(&polygon))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(isize, isize)]) -> Polygon {
+        points
+            .iter()
+            .map(|&p| p.into())
+            .collect::<Vec<Point>>()
+            .into()
+    }
+
+    fn area2(pieces: &[Piece]) -> i128 {
+        pieces.iter().map(Piece::area2).sum()
+    }
+
+    #[test]
+    fn rectilinear_in_either_orientation() {
+        let clockwise =
+            polygon(&[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]);
+        let counter_clockwise =
+            polygon(&[(2, 0), (2, 2), (1, 2), (1, 1), (0, 1), (0, 0)]);
+        for l in [clockwise, counter_clockwise] {
+            let pieces = Decomposer::decompose_octilinear(l).unwrap();
+            assert_eq!(pieces.len(), 2);
+            assert!(pieces.iter().all(|piece| matches!(piece, Piece::Rect(_))));
+            assert_eq!(area2(&pieces), 6);
+        }
+    }
+
+    #[test]
+    fn triangle() {
+        for points in [[(0, 0), (0, 2), (2, 0)], [(0, 0), (2, 0), (0, 2)]] {
+            let pieces =
+                Decomposer::decompose_octilinear(polygon(&points)).unwrap();
+            assert_eq!(
+                pieces,
+                vec![Piece::Trapezoid(Trapezoid {
+                    y_min: 0,
+                    y_max: 2,
+                    bottom: (0, 2),
+                    top: (0, 0),
+                })]
+            );
+            let Piece::Trapezoid(triangle) = pieces[0] else {
+                unreachable!()
+            };
+            assert!(triangle.is_triangle());
+            assert_eq!(triangle.points().len(), 3);
+            assert_eq!(triangle.bbox(), Rect::from_bounds(0, 0, 2, 2));
+        }
+    }
+
+    #[test]
+    fn octagon() {
+        // Corners cut by 1 off a 4 by 4 square, in both orientations.
+        let mut points = vec![
+            (0, 1),
+            (0, 3),
+            (1, 4),
+            (3, 4),
+            (4, 3),
+            (4, 1),
+            (3, 0),
+            (1, 0),
+        ];
+        for _ in 0..2 {
+            let octagon = polygon(&points);
+            let pieces = Decomposer::decompose_octilinear(octagon).unwrap();
+            assert_eq!(area2(&pieces), 2 * 14);
+            assert_eq!(
+                pieces
+                    .iter()
+                    .filter(|piece| matches!(piece, Piece::Rect(_)))
+                    .count(),
+                3
+            );
+            points.reverse();
+        }
+    }
+
+    #[test]
+    fn narrow_parallelograms_are_cut_into_triangles() {
+        let pieces = Decomposer::decompose_octilinear(polygon(&[
+            (0, 0),
+            (3, 3),
+            (4, 3),
+            (1, 0),
+        ]))
+        .unwrap();
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(
+            pieces[..2],
+            [
+                Piece::Trapezoid(Trapezoid {
+                    y_min: 0,
+                    y_max: 1,
+                    bottom: (0, 1),
+                    top: (1, 1),
+                }),
+                Piece::Trapezoid(Trapezoid {
+                    y_min: 0,
+                    y_max: 1,
+                    bottom: (1, 1),
+                    top: (1, 2),
+                }),
+            ]
+        );
+        assert_eq!(area2(&pieces), 6);
+
+        // Leaning the other way, 2 wide and 5 tall.
+        let pieces = Decomposer::decompose_octilinear(polygon(&[
+            (5, 0),
+            (0, 5),
+            (2, 5),
+            (7, 0),
+        ]))
+        .unwrap();
+        assert_eq!(area2(&pieces), 20);
+        assert_eq!(
+            pieces
+                .iter()
+                .filter(|piece| matches!(piece, Piece::Rect(_)))
+                .count(),
+            1
+        );
+        assert!(pieces.iter().all(|piece| match piece {
+            Piece::Rect(_) => true,
+            Piece::Trapezoid(trapezoid) => trapezoid.is_triangle(),
+        }));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Decomposer::decompose_octilinear(polygon(&[(0, 0), (0, 1)])),
+            Err(DecompErr::NotEnoughPoints)
+        );
+        assert_eq!(
+            Decomposer::decompose_octilinear(polygon(&[
+                (0, 0),
+                (0, 2),
+                (1, 0)
+            ])),
+            Err(DecompErr::NotOctilinear)
+        );
+    }
+}
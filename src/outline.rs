@@ -0,0 +1,470 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use crate::{geometry::Side, point::Point, rect::Rect};
+
+/// A half-open stretch `[start, end)` of a scanline band.
+pub(crate) type Interval = (isize, isize);
+
+/// The boundary of one connected component of a union of rects.
+///
+/// Every ring is in clockwise order, starts at its lowest (then leftmost)
+/// point, and does not repeat its first point, so it can be handed directly to
+/// `Geometry::new`. Holes are given as the clockwise outline of the region
+/// they cut out of `outer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub outer: Vec<Point>,
+    pub holes: Vec<Vec<Point>>,
+}
+
+/// Sorted, deduplicated y coordinates at which the region covered by `rects`
+/// can change. Degenerate rects are ignored.
+pub(crate) fn scanlines<'a>(
+    rects: impl IntoIterator<Item = &'a Rect>,
+) -> Vec<isize> {
+    let mut ys = rects
+        .into_iter()
+        .filter(|r| !r.is_degenerate())
+        .flat_map(|r| [r.y_min(), r.y_max()])
+        .collect::<Vec<isize>>();
+    ys.sort_unstable();
+    ys.dedup();
+    ys
+}
+
+/// For each band between consecutive `scanlines`, the sorted, disjoint
+/// intervals covered by the union of `rects`.
+///
+/// `scanlines` must contain the y bounds of every (non-degenerate) rect. The
+/// sweep mirrors the decomposer's main loop: rects become active when the
+/// scanline reaches their bottom, are purged once it reaches their top, and
+/// within a band the walls of active rects are walked from left to right.
+pub(crate) fn covered_bands(
+    rects: &[Rect],
+    scanlines: &[isize],
+) -> Vec<Vec<Interval>> {
+    let mut pending = rects
+        .iter()
+        .filter(|r| !r.is_degenerate())
+        .collect::<Vec<&Rect>>();
+    pending.sort_by_key(|r| r.y_min());
+    let mut pending = pending.into_iter().peekable();
+    let mut active: Vec<&Rect> = Vec::new();
+
+    scanlines
+        .windows(2)
+        .map(|band| {
+            let scanline = band[0];
+            active.retain(|r| r.y_max() > scanline);
+            while let Some(r) = pending.next_if(|r| r.y_min() <= scanline) {
+                active.push(r);
+            }
+
+            let mut walls = active
+                .iter()
+                .flat_map(|r| {
+                    [(r.x_min(), Side::Left), (r.x_max(), Side::Right)]
+                })
+                .collect::<Vec<(isize, Side)>>();
+            // Left walls sort before right walls at the same x, so that
+            // abutting rects merge into one interval.
+            walls.sort_by_key(|&(x, side)| (x, side == Side::Right));
+
+            let mut intervals = Vec::new();
+            let (mut depth, mut start) = (0_usize, 0_isize);
+            for (x, side) in walls {
+                match side {
+                    Side::Left => {
+                        if depth == 0 {
+                            start = x;
+                        }
+                        depth += 1;
+                    }
+                    Side::Right => {
+                        depth -= 1;
+                        if depth == 0 {
+                            push_interval(&mut intervals, (start, x));
+                        }
+                    }
+                }
+            }
+            intervals
+        })
+        .collect()
+}
+
+/// Combine two sorted, disjoint interval lists, keeping the stretches for
+/// which `keep(in_a, in_b)` holds.
+pub(crate) fn combine(
+    a: &[Interval],
+    b: &[Interval],
+    keep: impl Fn(bool, bool) -> bool,
+) -> Vec<Interval> {
+    let mut xs = a
+        .iter()
+        .chain(b)
+        .flat_map(|&(start, end)| [start, end])
+        .collect::<Vec<isize>>();
+    xs.sort_unstable();
+    xs.dedup();
+
+    let (mut i, mut j) = (0, 0);
+    let mut result = Vec::new();
+    for stretch in xs.windows(2) {
+        let (start, end) = (stretch[0], stretch[1]);
+        while i < a.len() && a[i].1 <= start {
+            i += 1;
+        }
+        while j < b.len() && b[j].1 <= start {
+            j += 1;
+        }
+        let in_a = i < a.len() && a[i].0 <= start;
+        let in_b = j < b.len() && b[j].0 <= start;
+        if keep(in_a, in_b) {
+            push_interval(&mut result, (start, end));
+        }
+    }
+    result
+}
+
+/// Push an interval onto a sorted list, merging it with the last one if they
+/// touch.
+pub(crate) fn push_interval(intervals: &mut Vec<Interval>, next: Interval) {
+    if let Some(last) = intervals.last_mut() {
+        if last.1 >= next.0 {
+            last.1 = last.1.max(next.1);
+            return;
+        }
+    }
+    intervals.push(next);
+}
+
+/// A sum of `i128` terms, kept exact as the 256-bit two's complement
+/// integer `high * 2^128 + low`. Comparing the fields in order compares the
+/// sums.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Area2 {
+    high: i128,
+    low: u128,
+}
+
+impl Area2 {
+    pub(crate) const ZERO: Area2 = Area2 { high: 0, low: 0 };
+
+    fn add(self, term: i128) -> Self {
+        let (low, carry) = self.low.overflowing_add(term as u128);
+        // The term is sign-extended into the high half.
+        let high = self
+            .high
+            .wrapping_add(if term < 0 { -1 } else { 0 })
+            .wrapping_add(i128::from(carry));
+        Self { high, low }
+    }
+
+    /// Half the magnitude of the sum, rounded down, if it fits in an
+    /// `i128`.
+    pub(crate) fn half_magnitude(self) -> Option<i128> {
+        let (high, low) = if self.high < 0 {
+            let (low, carry) = (!self.low).overflowing_add(1);
+            ((!self.high).wrapping_add(i128::from(carry)), low)
+        } else {
+            (self.high, self.low)
+        };
+        (high == 0).then_some((low >> 1) as i128)
+    }
+}
+
+/// Twice the signed area enclosed by a ring: negative if it is clockwise.
+///
+/// Each term of the shoelace formula fits in an `i128` whatever the
+/// coordinates, but their sum may not, so it is kept in an `Area2`.
+pub(crate) fn signed_area2(ring: &[Point]) -> Area2 {
+    ring.iter().zip(ring.iter().cycle().skip(1)).fold(
+        Area2::ZERO,
+        |sum, (p, q)| {
+            sum.add(p.x as i128 * q.y as i128)
+                .add(-(q.x as i128 * p.y as i128))
+        },
+    )
+}
+
+/// Area enclosed by a ring, whatever its winding (shoelace formula), or
+/// `None` if it does not fit in an `i128`. Only rings spanning most of the
+/// range of `isize` come close.
+#[inline]
+pub fn ring_area(ring: &[Point]) -> Option<i128> {
+    signed_area2(ring).half_magnitude()
+}
+
+impl Outline {
+    /// Area of the region: that of the outer ring minus those of the holes,
+    /// or `None` if one of them does not fit in an `i128`.
+    pub fn area(&self) -> Option<i128> {
+        self.holes
+            .iter()
+            .try_fold(ring_area(&self.outer)?, |area, hole| {
+                Some(area - ring_area(hole)?)
+            })
+    }
+
+    /// Compute the outlines of the union of `rects`, one per connected
+    /// component. Rects may overlap; degenerate rects are ignored.
+    ///
+    /// Boundary segments are oriented so that the covered region is always on
+    /// their right, which makes outer rings clockwise and hole rings
+    /// counter-clockwise, before the latter are flipped on output. Where two
+    /// pieces only touch at a corner, they are kept as separate rings.
+    pub fn from_rects(rects: &[Rect]) -> Vec<Outline> {
+        let ys = scanlines(rects);
+        let bands = covered_bands(rects, &ys);
+
+        let mut outgoing: BTreeMap<Point, Vec<Point>> = BTreeMap::new();
+        let mut add_segment = |source: Point, target: Point| {
+            outgoing.entry(source).or_default().push(target);
+        };
+
+        // Left walls go up, right walls go down.
+        for (intervals, band) in bands.iter().zip(ys.windows(2)) {
+            let (bottom, top) = (band[0], band[1]);
+            for &(x_min, x_max) in intervals {
+                add_segment(Point::new(x_min, bottom), Point::new(x_min, top));
+                add_segment(Point::new(x_max, top), Point::new(x_max, bottom));
+            }
+        }
+
+        // Where the band below is covered but the one above is not, the
+        // boundary runs towards +x; in the opposite case, towards -x.
+        let empty = Vec::new();
+        for (k, &y) in ys.iter().enumerate() {
+            let below = k.checked_sub(1).map_or(&empty, |j| &bands[j]);
+            let above = bands.get(k).unwrap_or(&empty);
+            for (x_min, x_max) in combine(below, above, |b, a| b && !a) {
+                add_segment(Point::new(x_min, y), Point::new(x_max, y));
+            }
+            for (x_min, x_max) in combine(below, above, |b, a| !b && a) {
+                add_segment(Point::new(x_max, y), Point::new(x_min, y));
+            }
+        }
+
+        let (outers, holes): (Vec<Vec<Point>>, Vec<Vec<Point>>) =
+            link_rings(outgoing)
+                .into_iter()
+                .partition(|ring| signed_area2(ring) < Area2::ZERO);
+
+        let mut outlines = outers
+            .into_iter()
+            .map(|mut outer| {
+                start_at_min(&mut outer);
+                Outline {
+                    outer,
+                    holes: Vec::new(),
+                }
+            })
+            .collect::<Vec<Outline>>();
+
+        for mut hole in holes {
+            start_at_min(&mut hole);
+            // The lowest, leftmost point of a hole is its lower left corner,
+            // so the hole's interior lies just above and to the right of it.
+            // Coordinates are doubled to test there without rounding.
+            let probe = (2 * hole[0].x + 1, 2 * hole[0].y + 1);
+            let owner = outlines
+                .iter()
+                .enumerate()
+                .filter(|(_, outline)| ring_contains(&outline.outer, probe))
+                .min_by_key(|(_, outline)| {
+                    Reverse(signed_area2(&outline.outer))
+                })
+                .map(|(ix, _)| ix);
+            hole.reverse();
+            start_at_min(&mut hole);
+            if let Some(ix) = owner {
+                outlines[ix].holes.push(hole);
+            }
+        }
+
+        outlines.sort_by_key(|outline| outline.outer[0]);
+        outlines
+    }
+}
+
+/// Chain directed boundary segments into closed rings, dropping the points
+/// where consecutive segments are collinear.
+fn link_rings(mut outgoing: BTreeMap<Point, Vec<Point>>) -> Vec<Vec<Point>> {
+    let mut rings = Vec::new();
+    // The lowest remaining point can never be a pinch point (that would need
+    // a boundary segment going further down), so starting there is safe.
+    while let Some(start) = outgoing.keys().next().copied() {
+        let mut ring = vec![start];
+        let (mut current, mut heading) = (start, None);
+        loop {
+            let next = take_next(&mut outgoing, current, heading);
+            heading = Some(direction(current, next));
+            if next == start {
+                break;
+            }
+            ring.push(next);
+            current = next;
+        }
+        rings.push(drop_collinear(ring));
+    }
+    rings
+}
+
+#[inline]
+fn direction(source: Point, target: Point) -> (isize, isize) {
+    (
+        (target.x - source.x).signum(),
+        (target.y - source.y).signum(),
+    )
+}
+
+/// Remove and return the segment leaving `current` that turns the most to the
+/// right relative to `heading`. Preferring right turns keeps regions which
+/// only touch at a corner in separate rings.
+fn take_next(
+    outgoing: &mut BTreeMap<Point, Vec<Point>>,
+    current: Point,
+    heading: Option<(isize, isize)>,
+) -> Point {
+    let targets = outgoing
+        .get_mut(&current)
+        .expect("boundary segments should always form closed rings");
+    let ix = match heading {
+        Some((hx, hy)) => (0..targets.len())
+            .min_by_key(|&ix| {
+                let (cx, cy) = direction(current, targets[ix]);
+                match (hx * cy - hy * cx, hx * cx + hy * cy) {
+                    (cross, _) if cross < 0 => 0,
+                    (0, dot) if dot > 0 => 1,
+                    (cross, _) if cross > 0 => 2,
+                    _ => 3,
+                }
+            })
+            .unwrap(),
+        None => 0,
+    };
+    let next = targets.swap_remove(ix);
+    if targets.is_empty() {
+        outgoing.remove(&current);
+    }
+    next
+}
+
+pub(crate) fn drop_collinear(ring: Vec<Point>) -> Vec<Point> {
+    let n = ring.len();
+    (0..n)
+        .filter(|&ix| {
+            let (prev, here, next) =
+                (ring[(ix + n - 1) % n], ring[ix], ring[(ix + 1) % n]);
+            !((prev.x == here.x && here.x == next.x)
+                || (prev.y == here.y && here.y == next.y))
+        })
+        .map(|ix| ring[ix])
+        .collect()
+}
+
+pub(crate) fn start_at_min(ring: &mut [Point]) {
+    if let Some(ix) = (0..ring.len()).min_by_key(|&ix| ring[ix]) {
+        ring.rotate_left(ix);
+    }
+}
+
+/// Even-odd test of a point given in doubled coordinates against a ring. The
+/// point must not lie on the (doubled) ring.
+fn ring_contains(ring: &[Point], (px, py): (isize, isize)) -> bool {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .filter(|(p, q)| {
+            p.x == q.x
+                && 2 * p.x > px
+                && (2 * p.y).min(2 * q.y) < py
+                && py < (2 * p.y).max(2 * q.y)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn l_shape() {
+        let outlines = Outline::from_rects(&[
+            Rect::from_bounds(0, 0, 2, 1),
+            Rect::from_bounds(1, 1, 2, 2),
+        ]);
+        assert_eq!(
+            outlines,
+            vec![Outline {
+                outer: ring(&[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]),
+                holes: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn overlapping_rects_merge() {
+        let outlines = Outline::from_rects(&[
+            Rect::from_bounds(0, 0, 2, 2),
+            Rect::from_bounds(1, 1, 3, 3),
+        ]);
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(
+            outlines[0].outer,
+            ring(&[
+                (0, 0),
+                (0, 2),
+                (1, 2),
+                (1, 3),
+                (3, 3),
+                (3, 1),
+                (2, 1),
+                (2, 0)
+            ])
+        );
+        assert_eq!(outlines[0].area(), Some(7));
+    }
+
+    #[test]
+    fn corner_touching_rects_stay_apart() {
+        let outlines = Outline::from_rects(&[
+            Rect::from_bounds(0, 0, 1, 1),
+            Rect::from_bounds(1, 1, 2, 2),
+        ]);
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].outer, ring(&[(0, 0), (0, 1), (1, 1), (1, 0)]));
+        assert_eq!(outlines[1].outer, ring(&[(1, 1), (1, 2), (2, 2), (2, 1)]));
+    }
+
+    #[test]
+    fn frame_has_a_hole() {
+        let outlines = Outline::from_rects(&[
+            Rect::from_bounds(0, 0, 3, 1),
+            Rect::from_bounds(0, 2, 3, 3),
+            Rect::from_bounds(0, 0, 1, 3),
+            Rect::from_bounds(2, 0, 3, 3),
+        ]);
+        assert_eq!(
+            outlines,
+            vec![Outline {
+                outer: ring(&[(0, 0), (0, 3), (3, 3), (3, 0)]),
+                holes: vec![ring(&[(1, 1), (1, 2), (2, 2), (2, 1)])],
+            }]
+        );
+        assert_eq!(outlines[0].area(), Some(8));
+    }
+
+    #[test]
+    fn degenerate_rects_are_ignored() {
+        assert!(
+            Outline::from_rects(&[Rect::from_bounds(0, 0, 0, 5)]).is_empty()
+        );
+    }
+}
@@ -2,7 +2,13 @@ use std::cmp::Ordering;
 
 use crate::geometry::Side;
 
+/// Serialized compactly as `[x, y]`.
 #[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "(isize, isize)", into = "(isize, isize)")
+)]
 pub struct Point {
     pub x: isize,
     pub y: isize,
@@ -35,6 +41,20 @@ impl Point {
     }
 }
 
+impl From<(isize, isize)> for Point {
+    #[inline]
+    fn from((x, y): (isize, isize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Point> for (isize, isize) {
+    #[inline]
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
+}
+
 impl PartialEq for Point {
     fn eq(&self, other: &Self) -> bool {
         self.x.eq(&other.x) && self.y.eq(&other.y)
@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+
+use crate::{
+    geometry::Geometry,
+    outline::{ring_area, signed_area2, Area2},
+    point::Point,
+    rect::Rect,
+};
+
+/// The direction a ring turns in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// A polygon given by the ring of its vertices, without the closing vertex.
+///
+/// Areas and orientations are computed exactly whatever the coordinates,
+/// although an area may not fit in the `i128` it is returned in. Centroids
+/// are computed in `f64`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polygon {
+    points: Vec<Point>,
+}
+
+impl Polygon {
+    #[inline]
+    pub fn new(points: Vec<Point>) -> Self {
+        Self { points }
+    }
+
+    #[inline]
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    #[inline]
+    pub fn into_points(self) -> Vec<Point> {
+        self.points
+    }
+
+    #[inline]
+    pub fn vertex_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// The edges of the ring, including the closing one.
+    fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.points.iter().zip(self.points.iter().cycle().skip(1))
+    }
+
+    /// The area enclosed by the ring (shoelace formula), whatever its
+    /// winding, or `None` if it does not fit in an `i128`.
+    #[inline]
+    pub fn area(&self) -> Option<i128> {
+        ring_area(&self.points)
+    }
+
+    /// The euclidean length of the ring, exact for rectilinear polygons of
+    /// perimeter up to 2^53.
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(p, q)| {
+                ((q.x as i128 - p.x as i128) as f64)
+                    .hypot((q.y as i128 - p.y as i128) as f64)
+            })
+            .sum()
+    }
+
+    /// The smallest rect containing the polygon.
+    #[inline]
+    pub fn bbox(&self) -> Rect {
+        Rect::bounding(&self.points)
+    }
+
+    /// The centroid of the enclosed region, or `None` if it has no area.
+    ///
+    /// The ring is taken relative to its first vertex, and the area and
+    /// weighted sums accumulated in `f64`, since the weighted sums would
+    /// overflow `i128` for large coordinates. Each cross product is computed
+    /// in `i128` where it fits, and in `f64` otherwise.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if signed_area2(&self.points) == Area2::ZERO {
+            return None;
+        }
+        let origin = self.points[0];
+        let relative = |p: &Point| {
+            (
+                p.x as i128 - origin.x as i128,
+                p.y as i128 - origin.y as i128,
+            )
+        };
+        let (mut area2, mut cx, mut cy) = (0.0, 0.0, 0.0);
+        for (p, q) in self.edges() {
+            let ((px, py), (qx, qy)) = (relative(p), relative(q));
+            let cross = px
+                .checked_mul(qy)
+                .zip(qx.checked_mul(py))
+                .and_then(|(a, b)| a.checked_sub(b))
+                .map_or_else(
+                    || px as f64 * qy as f64 - qx as f64 * py as f64,
+                    |cross| cross as f64,
+                );
+            area2 += cross;
+            cx += (px + qx) as f64 * cross;
+            cy += (py + qy) as f64 * cross;
+        }
+        let area6 = 3.0 * area2;
+        Some((origin.x as f64 + cx / area6, origin.y as f64 + cy / area6))
+    }
+
+    /// The winding of the ring, or `None` if it encloses no area.
+    pub fn orientation(&self) -> Option<Winding> {
+        match signed_area2(&self.points).cmp(&Area2::ZERO) {
+            Ordering::Equal => None,
+            Ordering::Less => Some(Winding::Clockwise),
+            Ordering::Greater => Some(Winding::CounterClockwise),
+        }
+    }
+
+    /// Whether every edge, including the closing one, is horizontal or
+    /// vertical.
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|(p, q)| p.x == q.x || p.y == q.y)
+    }
+
+    /// Whether every edge, including the closing one, is horizontal,
+    /// vertical, or at 45 degrees.
+    pub fn is_octilinear(&self) -> bool {
+        self.edges().all(|(p, q)| {
+            p.x == q.x
+                || p.y == q.y
+                || (q.x as i128 - p.x as i128).abs()
+                    == (q.y as i128 - p.y as i128).abs()
+        })
+    }
+}
+
+impl From<Vec<Point>> for Polygon {
+    #[inline]
+    fn from(points: Vec<Point>) -> Self {
+        Self::new(points)
+    }
+}
+
+impl From<&[Point]> for Polygon {
+    #[inline]
+    fn from(points: &[Point]) -> Self {
+        Self::new(points.to_vec())
+    }
+}
+
+impl From<Polygon> for Vec<Point> {
+    #[inline]
+    fn from(polygon: Polygon) -> Self {
+        polygon.points
+    }
+}
+
+/// The polygon a geometry was built from, without the nodes `split_edge`
+/// adds.
+impl From<&Geometry> for Polygon {
+    #[inline]
+    fn from(geometry: &Geometry) -> Self {
+        Self::new(geometry.boundary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(isize, isize)]) -> Polygon {
+        points
+            .iter()
+            .map(|&p| p.into())
+            .collect::<Vec<Point>>()
+            .into()
+    }
+
+    fn l_shape() -> Polygon {
+        polygon(&[(0, 0), (0, 2), (1, 2), (1, 1), (2, 1), (2, 0)])
+    }
+
+    #[test]
+    fn measurements() {
+        let l = l_shape();
+        assert_eq!(l.vertex_count(), 6);
+        assert_eq!(l.area(), Some(3));
+        assert_eq!(l.perimeter(), 8.0);
+        assert_eq!(l.bbox(), Rect::from_bounds(0, 0, 2, 2));
+        assert_eq!(l.centroid(), Some((5.0 / 6.0, 5.0 / 6.0)));
+        assert_eq!(l.orientation(), Some(Winding::Clockwise));
+        assert!(l.is_rectilinear() && l.is_octilinear());
+
+        let mut points = l.into_points();
+        points.reverse();
+        let l = Polygon::from(points.as_slice());
+        assert_eq!(l.orientation(), Some(Winding::CounterClockwise));
+        assert_eq!(l.area(), Some(3));
+
+        let flat = polygon(&[(0, 0), (0, 2), (0, 1)]);
+        assert_eq!(flat.orientation(), None);
+        assert_eq!(flat.centroid(), None);
+    }
+
+    #[test]
+    fn slanted_edges() {
+        let triangle = polygon(&[(0, 0), (0, 2), (2, 0)]);
+        assert!(!triangle.is_rectilinear() && triangle.is_octilinear());
+        assert_eq!(triangle.perimeter(), 4.0 + 8.0_f64.sqrt());
+        assert!(!polygon(&[(0, 0), (0, 2), (1, 0)]).is_octilinear());
+    }
+
+    #[test]
+    fn large_coordinates() {
+        // Twice the area of this square is 2^127, one more than fits in an
+        // i128.
+        let m = 1 << 62;
+        let square = polygon(&[(-m, -m), (-m, m), (m, m), (m, -m)]);
+        assert_eq!(square.area(), Some(1 << 126));
+        assert_eq!(square.centroid(), Some((0.0, 0.0)));
+        let mut points = square.into_points();
+        points.reverse();
+        let square = Polygon::new(points);
+        assert_eq!(square.area(), Some(1 << 126));
+        assert_eq!(square.centroid(), Some((0.0, 0.0)));
+
+        let far = polygon(&[(m - 2, m - 2), (m - 2, m), (m, m), (m, m - 2)]);
+        assert_eq!(far.area(), Some(4));
+        assert_eq!(far.centroid(), Some(((m - 1) as f64, (m - 1) as f64)));
+
+        // Twice the area of the widest square is almost 2^129.
+        let (lo, hi) = (isize::MIN, isize::MAX);
+        let mut square = polygon(&[(lo, lo), (lo, hi), (hi, hi), (hi, lo)]);
+        assert_eq!(square.orientation(), Some(Winding::Clockwise));
+        assert_eq!(square.area(), None);
+        let (x, y) = square.centroid().unwrap();
+        assert!(x.abs() < 1e6 && y.abs() < 1e6);
+        let triangle = polygon(&[(lo, lo), (lo, hi), (hi, lo)]);
+        assert_eq!(triangle.orientation(), Some(Winding::Clockwise));
+        assert_eq!(
+            triangle.area(),
+            Some((u128::from(u64::MAX).pow(2) / 2) as i128)
+        );
+        square.points.reverse();
+        assert_eq!(square.orientation(), Some(Winding::CounterClockwise));
+        let sliver = polygon(&[(lo, 0), (lo, 1), (hi, 1), (hi, 0)]);
+        assert_eq!(sliver.area(), Some(u64::MAX as i128));
+    }
+
+    #[test]
+    fn conversions() {
+        let l = l_shape();
+        let points: Vec<Point> = l.clone().into();
+        assert_eq!(Polygon::from(points), l);
+        let geometry = Geometry::new(l.points().to_vec()).unwrap();
+        assert_eq!(Polygon::from(&geometry), l);
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    edge::Edge,
+    geometry::Geometry,
+    node::NodeId,
+    outline::{combine, push_interval, Interval},
+    point::Point,
+    rect::Rect,
+};
+
+/// Where an end point of a wall came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexOrigin {
+    /// The vertex at this index of the points passed to the decomposer.
+    Original(usize),
+    /// A vertex created by `Geometry::split_edge` where the scanline cut
+    /// through an edge.
+    Split,
+}
+
+/// What the top or bottom of a decomposed rect lies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SideKind {
+    /// Entirely on the polygon's boundary.
+    Boundary,
+    /// Entirely on a cut introduced by the scanline.
+    Cut,
+    /// Partly on the boundary, partly on a cut.
+    Mixed,
+}
+
+/// The (vertical) polygon edge a left or right wall of a rect lies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Wall {
+    /// The polygon edge, as the index of its first vertex in the points
+    /// passed to the decomposer: the edge runs from that vertex to the next.
+    /// The wall may only cover part of it, where the scanline split it.
+    pub edge: usize,
+    pub source: VertexOrigin,
+    pub target: VertexOrigin,
+}
+
+/// A rect of the decomposition, together with the polygon edges it came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecomposedRect {
+    pub rect: Rect,
+    pub left: Wall,
+    pub right: Wall,
+    pub top: SideKind,
+    pub bottom: SideKind,
+}
+
+/// The horizontal edges of a polygon, indexed by their y coordinate.
+///
+/// `Geometry` only stores vertical edges (the ones the scanline intersects),
+/// so this is what tells the boundary apart from cuts on the horizontal sides
+/// of rects.
+#[derive(Clone, Debug, Default)]
+pub struct HorizontalBoundary {
+    edges: BTreeMap<isize, Vec<Interval>>,
+}
+
+impl HorizontalBoundary {
+    /// Collect the horizontal edges of the polygon with the given points.
+    pub fn new(points: &[Point]) -> Self {
+        let mut by_y: BTreeMap<isize, Vec<Interval>> = BTreeMap::new();
+        for (p, q) in points.iter().zip(points.iter().cycle().skip(1)) {
+            if p.y == q.y && p.x != q.x {
+                by_y.entry(p.y)
+                    .or_default()
+                    .push((p.x.min(q.x), p.x.max(q.x)));
+            }
+        }
+        let edges = by_y
+            .into_iter()
+            .map(|(y, mut intervals)| {
+                intervals.sort_unstable();
+                let mut merged = Vec::with_capacity(intervals.len());
+                for interval in intervals {
+                    push_interval(&mut merged, interval);
+                }
+                (y, merged)
+            })
+            .collect();
+        Self { edges }
+    }
+
+    /// Recover the horizontal edges from the nodes a geometry was built with.
+    pub fn from_geometry(geometry: &Geometry, original_nodes: usize) -> Self {
+        Self::new(
+            &geometry
+                .iter_nodes()
+                .take(original_nodes)
+                .map(|(_, node)| node.point)
+                .collect::<Vec<Point>>(),
+        )
+    }
+
+    /// Classify the horizontal stretch `[x_min, x_max]` at height `y`.
+    pub fn classify(&self, y: isize, x_min: isize, x_max: isize) -> SideKind {
+        let covered: isize = self
+            .edges
+            .get(&y)
+            .map(|intervals| {
+                combine(intervals, &[(x_min, x_max)], |on_boundary, on_side| {
+                    on_boundary && on_side
+                })
+                .iter()
+                .map(|(start, end)| end - start)
+                .sum()
+            })
+            .unwrap_or(0);
+        match covered {
+            0 => SideKind::Cut,
+            c if c == x_max - x_min => SideKind::Boundary,
+            _ => SideKind::Mixed,
+        }
+    }
+}
+
+impl Wall {
+    /// `original_nodes` is the number of points the geometry was built from:
+    /// those are allocated first, so any node past them came from a split.
+    pub fn new(edge: &Edge, original_nodes: usize) -> Self {
+        let origin = |id: NodeId| match id.index() {
+            ix if ix < original_nodes => VertexOrigin::Original(ix),
+            _ => VertexOrigin::Split,
+        };
+        Self {
+            edge: edge.origin,
+            source: origin(edge.source),
+            target: origin(edge.target),
+        }
+    }
+}
+
+impl DecomposedRect {
+    pub fn new(
+        rect: Rect,
+        left: &Edge,
+        right: &Edge,
+        original_nodes: usize,
+        boundary: &HorizontalBoundary,
+    ) -> Self {
+        Self {
+            rect,
+            left: Wall::new(left, original_nodes),
+            right: Wall::new(right, original_nodes),
+            top: boundary.classify(rect.y_max(), rect.x_min(), rect.x_max()),
+            bottom: boundary.classify(rect.y_min(), rect.x_min(), rect.x_max()),
+        }
+    }
+
+    /// Whether the left wall is (part of) an edge that was split by the
+    /// scanline.
+    #[inline]
+    pub fn left_is_split(&self) -> bool {
+        self.left.source == VertexOrigin::Split
+            || self.left.target == VertexOrigin::Split
+    }
+
+    /// Whether the right wall is (part of) an edge that was split by the
+    /// scanline.
+    #[inline]
+    pub fn right_is_split(&self) -> bool {
+        self.right.source == VertexOrigin::Split
+            || self.right.target == VertexOrigin::Split
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decomposer::Decomposer;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// A staircase whose left edge (from vertex 0) is cut twice.
+    fn staircase() -> Vec<Point> {
+        ring(&[
+            (0, 0),
+            (0, 30),
+            (3, 30),
+            (3, 20),
+            (2, 20),
+            (2, 10),
+            (1, 10),
+            (1, 0),
+        ])
+    }
+
+    #[test]
+    fn split_walls_keep_their_polygon_edge() {
+        let mut rects =
+            Decomposer::decompose_with_provenance(staircase()).unwrap();
+        rects.sort_by_key(|decomposed| decomposed.rect.y_min());
+        assert_eq!(
+            rects
+                .iter()
+                .map(|decomposed| decomposed.rect)
+                .collect::<Vec<Rect>>(),
+            vec![
+                Rect::from_bounds(0, 0, 1, 10),
+                Rect::from_bounds(0, 10, 2, 20),
+                Rect::from_bounds(0, 20, 3, 30),
+            ]
+        );
+        assert!(rects.iter().all(|decomposed| decomposed.left.edge == 0));
+        assert_eq!(
+            rects
+                .iter()
+                .map(|decomposed| decomposed.right.edge)
+                .collect::<Vec<usize>>(),
+            vec![6, 4, 2]
+        );
+        assert_eq!(
+            rects[1].left,
+            Wall {
+                edge: 0,
+                source: VertexOrigin::Split,
+                target: VertexOrigin::Split,
+            }
+        );
+        assert_eq!(rects[0].left.source, VertexOrigin::Original(0));
+        assert!(rects.iter().all(DecomposedRect::left_is_split));
+        assert!(!rects[0].right_is_split());
+    }
+
+    #[test]
+    fn horizontal_sides() {
+        let mut rects =
+            Decomposer::decompose_with_provenance(staircase()).unwrap();
+        rects.sort_by_key(|decomposed| decomposed.rect.y_min());
+        assert_eq!(rects[0].bottom, SideKind::Boundary);
+        assert_eq!(rects[0].top, SideKind::Cut);
+        assert_eq!(rects[2].bottom, SideKind::Mixed);
+        assert_eq!(rects[2].top, SideKind::Boundary);
+    }
+
+    #[test]
+    fn classify() {
+        let boundary = HorizontalBoundary::new(&staircase());
+        assert_eq!(boundary.classify(10, 1, 2), SideKind::Boundary);
+        assert_eq!(boundary.classify(10, 0, 2), SideKind::Mixed);
+        assert_eq!(boundary.classify(10, 0, 1), SideKind::Cut);
+    }
+}
@@ -3,7 +3,13 @@
 
 use crate::point::Point;
 
-#[derive(Clone, Copy, Debug, Default)]
+/// Serialized compactly as `[x_min, y_min, x_max, y_max]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "[isize; 4]", into = "[isize; 4]")
+)]
 pub struct Rect {
     _left: Point,
     _right: Point,
@@ -16,4 +22,143 @@ impl Rect {
             _right: right,
         }
     }
+
+    /// Build a rect from its bounds, normalizing them so that the lower left
+    /// corner is stored as `left`, and the upper right as `right`.
+    pub fn from_bounds(
+        x_min: isize,
+        y_min: isize,
+        x_max: isize,
+        y_max: isize,
+    ) -> Self {
+        Self::new(
+            Point::new(x_min.min(x_max), y_min.min(y_max)),
+            Point::new(x_min.max(x_max), y_min.max(y_max)),
+        )
+    }
+
+    /// The smallest rect containing every point, or a degenerate rect at the
+    /// origin if there are none.
+    pub fn bounding(points: &[Point]) -> Self {
+        points
+            .iter()
+            .map(|&p| Rect::new(p, p))
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn left(&self) -> Point {
+        self._left
+    }
+
+    #[inline]
+    pub fn right(&self) -> Point {
+        self._right
+    }
+
+    #[inline]
+    pub fn x_min(&self) -> isize {
+        self._left.x.min(self._right.x)
+    }
+
+    #[inline]
+    pub fn x_max(&self) -> isize {
+        self._left.x.max(self._right.x)
+    }
+
+    #[inline]
+    pub fn y_min(&self) -> isize {
+        self._left.y.min(self._right.y)
+    }
+
+    #[inline]
+    pub fn y_max(&self) -> isize {
+        self._left.y.max(self._right.y)
+    }
+
+    #[inline]
+    pub fn width(&self) -> isize {
+        self.x_max() - self.x_min()
+    }
+
+    #[inline]
+    pub fn height(&self) -> isize {
+        self.y_max() - self.y_min()
+    }
+
+    /// Area, widened so that large rects do not overflow.
+    #[inline]
+    pub fn area(&self) -> i128 {
+        self.width() as i128 * self.height() as i128
+    }
+
+    /// A rect with zero width or height covers no area.
+    #[inline]
+    pub fn is_degenerate(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    /// Whether `point` lies inside the rect or on its boundary.
+    #[inline]
+    pub fn contains(&self, point: &Point) -> bool {
+        self.x_min() <= point.x
+            && point.x <= self.x_max()
+            && self.y_min() <= point.y
+            && point.y <= self.y_max()
+    }
+
+    /// The rect mirrored about the diagonal `x == y`.
+    #[inline]
+    pub fn transposed(&self) -> Rect {
+        Rect::from_bounds(
+            self.y_min(),
+            self.x_min(),
+            self.y_max(),
+            self.x_max(),
+        )
+    }
+
+    /// Whether the rects share at least one point, boundary included.
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x_min() <= other.x_max()
+            && other.x_min() <= self.x_max()
+            && self.y_min() <= other.y_max()
+            && other.y_min() <= self.y_max()
+    }
+
+    /// The smallest rect containing both rects.
+    #[inline]
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::from_bounds(
+            self.x_min().min(other.x_min()),
+            self.y_min().min(other.y_min()),
+            self.x_max().max(other.x_max()),
+            self.y_max().max(other.y_max()),
+        )
+    }
+
+    /// Squared euclidean distance from `point` to the closest point of the
+    /// rect, zero if the rect contains it.
+    #[inline]
+    pub fn distance2(&self, point: &Point) -> i128 {
+        let dx = (self.x_min() - point.x).max(point.x - self.x_max()).max(0);
+        let dy = (self.y_min() - point.y).max(point.y - self.y_max()).max(0);
+        dx as i128 * dx as i128 + dy as i128 * dy as i128
+    }
+}
+
+impl From<[isize; 4]> for Rect {
+    #[inline]
+    fn from([x_min, y_min, x_max, y_max]: [isize; 4]) -> Self {
+        Self::from_bounds(x_min, y_min, x_max, y_max)
+    }
+}
+
+impl From<Rect> for [isize; 4] {
+    #[inline]
+    fn from(rect: Rect) -> Self {
+        [rect.x_min(), rect.y_min(), rect.x_max(), rect.y_max()]
+    }
 }
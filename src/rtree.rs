@@ -0,0 +1,302 @@
+// Based on:
+// Leutenegger, Lopez, Edgington, "STR: A Simple and Efficient Algorithm for
+// R-Tree Packing" (1997)
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    rect::Rect,
+};
+
+/// Most children a node of an `RTree` is packed with.
+pub const NODE_CAPACITY: usize = 16;
+
+#[derive(Clone, Debug)]
+struct Node {
+    bbox: Rect,
+    /// Indices into `RTree::entries` for leaves, into `RTree::nodes`
+    /// otherwise.
+    children: Vec<usize>,
+    leaf: bool,
+}
+
+/// A static R-tree over rects, each carrying a payload (e.g. the id of the
+/// polygon it was decomposed from).
+///
+/// The tree is bulk-loaded with Sort-Tile-Recursive packing, and is not
+/// updated afterwards. Queries treat rects as closed, so rects touching a
+/// window or point are reported.
+#[derive(Clone, Debug)]
+pub struct RTree<T> {
+    entries: Vec<(Rect, T)>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+impl<T> Default for RTree<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            nodes: Vec::new(),
+            root: None,
+        }
+    }
+}
+
+#[inline]
+fn center2(rect: &Rect) -> (isize, isize) {
+    (rect.x_min() + rect.x_max(), rect.y_min() + rect.y_max())
+}
+
+impl<T> RTree<T> {
+    pub fn bulk_load(entries: Vec<(Rect, T)>) -> Self {
+        let mut tree = Self {
+            entries,
+            ..Default::default()
+        };
+        let mut level = (0..tree.entries.len())
+            .map(|ix| (tree.entries[ix].0, ix))
+            .collect::<Vec<(Rect, usize)>>();
+        let mut leaf = true;
+        while level.len() > 1 || (leaf && !level.is_empty()) {
+            level = tree.pack(level, leaf);
+            leaf = false;
+        }
+        tree.root = level.first().map(|&(_, ix)| ix);
+        tree
+    }
+
+    /// Group `items` into nodes of at most `NODE_CAPACITY`: sort them by x,
+    /// cut them into vertical slices of about `sqrt(nodes)` nodes each, and
+    /// fill each slice's nodes in y order. Returns the new nodes.
+    fn pack(
+        &mut self,
+        mut items: Vec<(Rect, usize)>,
+        leaf: bool,
+    ) -> Vec<(Rect, usize)> {
+        let nodes = items.len().div_ceil(NODE_CAPACITY);
+        let slices = (nodes as f64).sqrt().ceil() as usize;
+        let per_slice = slices * NODE_CAPACITY;
+
+        items.sort_unstable_by_key(|(rect, _)| center2(rect).0);
+        let mut packed = Vec::with_capacity(nodes);
+        for slice in items.chunks_mut(per_slice) {
+            slice.sort_unstable_by_key(|(rect, _)| center2(rect).1);
+            for group in slice.chunks(NODE_CAPACITY) {
+                let bbox = group
+                    .iter()
+                    .map(|(rect, _)| *rect)
+                    .reduce(|a, b| a.union(&b))
+                    .unwrap();
+                self.nodes.push(Node {
+                    bbox,
+                    children: group.iter().map(|&(_, ix)| ix).collect(),
+                    leaf,
+                });
+                packed.push((bbox, self.nodes.len() - 1));
+            }
+        }
+        packed
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Rect, T)> {
+        self.entries.iter()
+    }
+
+    /// Every entry whose rect intersects `window`.
+    pub fn window(&self, window: &Rect) -> Vec<&(Rect, T)> {
+        let mut found = Vec::new();
+        let mut stack = self.root.into_iter().collect::<Vec<usize>>();
+        while let Some(ix) = stack.pop() {
+            let node = &self.nodes[ix];
+            if !node.bbox.intersects(window) {
+                continue;
+            }
+            if node.leaf {
+                found.extend(
+                    node.children
+                        .iter()
+                        .map(|&entry| &self.entries[entry])
+                        .filter(|(rect, _)| rect.intersects(window)),
+                );
+            } else {
+                stack.extend(node.children.iter().copied());
+            }
+        }
+        found
+    }
+
+    /// Every entry whose rect contains `point`.
+    pub fn at_point(&self, point: Point) -> Vec<&(Rect, T)> {
+        self.window(&Rect::new(point, point))
+    }
+
+    /// The `k` entries closest to `point`, nearest first, along with their
+    /// squared distance to it. Entries containing the point are at distance
+    /// zero.
+    pub fn nearest(&self, point: Point, k: usize) -> Vec<(i128, &(Rect, T))> {
+        // Nodes and entries are popped in order of distance; at equal
+        // distances entries come first (`false < true`).
+        let mut queue = BinaryHeap::new();
+        if let Some(root) = self.root {
+            queue.push(Reverse((
+                self.nodes[root].bbox.distance2(&point),
+                true,
+                root,
+            )));
+        }
+        let mut found = Vec::with_capacity(k);
+        while let Some(Reverse((distance, is_node, ix))) = queue.pop() {
+            if found.len() == k {
+                break;
+            }
+            if !is_node {
+                found.push((distance, &self.entries[ix]));
+                continue;
+            }
+            let node = &self.nodes[ix];
+            for &child in &node.children {
+                let rect = if node.leaf {
+                    &self.entries[child].0
+                } else {
+                    &self.nodes[child].bbox
+                };
+                queue.push(Reverse((
+                    rect.distance2(&point),
+                    !node.leaf,
+                    child,
+                )));
+            }
+        }
+        found
+    }
+}
+
+impl Decomposer {
+    /// Decompose each polygon and index all the resulting rects, each
+    /// carrying the id of its polygon.
+    pub fn index<T: Clone>(
+        polygons: impl IntoIterator<Item = (T, Vec<Point>)>,
+    ) -> Result<RTree<T>, DecompErr> {
+        let mut entries = Vec::new();
+        for (id, points) in polygons {
+            entries.extend(
+                Decomposer::decompose(points)?
+                    .into_iter()
+                    .map(|rect| (rect, id.clone())),
+            );
+        }
+        Ok(RTree::bulk_load(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 20 by 20 array of 3 by 2 rects, 5 apart, deep enough for three
+    /// levels of nodes.
+    fn array() -> RTree<usize> {
+        let entries = (0..400)
+            .map(|ix| {
+                let (x, y) = (5 * (ix % 20) as isize, 5 * (ix / 20) as isize);
+                (Rect::from_bounds(x, y, x + 3, y + 2), ix)
+            })
+            .collect();
+        RTree::bulk_load(entries)
+    }
+
+    fn ids<'a>(
+        entries: impl IntoIterator<Item = &'a (Rect, usize)>,
+    ) -> Vec<usize> {
+        let mut ids = entries
+            .into_iter()
+            .map(|(_, id)| *id)
+            .collect::<Vec<usize>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn windows_match_a_linear_scan() {
+        let tree = array();
+        assert_eq!(tree.len(), 400);
+        for window in [
+            Rect::from_bounds(3, 2, 5, 5),
+            Rect::from_bounds(-10, -10, -1, 200),
+            Rect::from_bounds(12, 17, 48, 61),
+            Rect::from_bounds(0, 0, 100, 100),
+        ] {
+            assert_eq!(
+                ids(tree.window(&window)),
+                ids(tree.iter().filter(|(rect, _)| rect.intersects(&window)))
+            );
+        }
+        // Closed rects: a window touching four corners reports all four.
+        assert_eq!(
+            ids(tree.window(&Rect::from_bounds(3, 2, 5, 5))),
+            [0, 1, 20, 21]
+        );
+        assert_eq!(ids(tree.at_point(Point::new(8, 7))), [21]);
+        assert!(tree.at_point(Point::new(4, 4)).is_empty());
+    }
+
+    #[test]
+    fn nearest_matches_a_linear_scan() {
+        let tree = array();
+        for (point, k) in [(Point::new(51, 48), 7), (Point::new(-30, 200), 3)] {
+            let mut expected = tree
+                .iter()
+                .map(|entry| entry.0.distance2(&point))
+                .collect::<Vec<i128>>();
+            expected.sort_unstable();
+            expected.truncate(k);
+            let found = tree.nearest(point, k);
+            assert_eq!(
+                found
+                    .iter()
+                    .map(|(distance, _)| *distance)
+                    .collect::<Vec<i128>>(),
+                expected
+            );
+            assert!(found.iter().all(|(distance, (rect, _))| rect
+                .distance2(&point)
+                == *distance));
+        }
+        assert_eq!(tree.nearest(Point::new(1, 1), 1)[0].0, 0);
+        assert!(RTree::<usize>::default()
+            .nearest(Point::new(0, 0), 3)
+            .is_empty());
+    }
+
+    #[test]
+    fn index_carries_polygon_ids() {
+        let l = [(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (2, 0)]
+            .map(Point::from)
+            .to_vec();
+        let shifted = l.iter().map(|p| Point::new(p.x + 10, p.y)).collect();
+        let tree = Decomposer::index([("a", l), ("b", shifted)]).unwrap();
+        assert_eq!(tree.len(), 4);
+        let at = |x, y| {
+            tree.at_point(Point::new(x, y))
+                .iter()
+                .map(|(_, id)| *id)
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(at(1, 0), ["a"]);
+        assert_eq!(at(11, 2), ["b"]);
+        assert!(Decomposer::index([(0, vec![Point::new(0, 0)])]).is_err());
+    }
+}
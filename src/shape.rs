@@ -0,0 +1,203 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    outline::{drop_collinear, signed_area2, start_at_min, Area2, Outline},
+    point::Point,
+    rect::Rect,
+};
+
+/// Why shapes read from a text format (WKT, GeoJSON, ...) cannot be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeErr {
+    /// Malformed input, with a description of what was expected where.
+    Syntax(String),
+    /// A geometry type other than the polygons this crate handles.
+    Unsupported(String),
+    /// A coordinate that is not an integer.
+    NotIntegral(String),
+    /// A coordinate that does not fit in an `isize`.
+    OutOfRange(String),
+    /// An edge that is neither horizontal nor vertical.
+    NotRectilinear(Point, Point),
+    /// A ring enclosing no area.
+    NotEnoughPoints,
+}
+
+impl Display for ShapeErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for ShapeErr {}
+
+/// A coordinate read as a float (`text`), if it is a whole number in the
+/// range of `isize`.
+pub(crate) fn float_coordinate(
+    value: f64,
+    text: &str,
+) -> Result<isize, ShapeErr> {
+    if !value.is_finite() || value.fract() != 0.0 {
+        Err(ShapeErr::NotIntegral(text.to_string()))
+    } else if value < isize::MIN as f64 || value >= -(isize::MIN as f64) {
+        // Unlike `isize::MAX`, both bounds are exact as floats.
+        Err(ShapeErr::OutOfRange(text.to_string()))
+    } else {
+        Ok(value as isize)
+    }
+}
+
+/// Parse a coordinate, accepting decimal notation only for whole numbers.
+pub(crate) fn parse_coordinate(text: &str) -> Result<isize, ShapeErr> {
+    match (text.parse::<isize>(), text.parse::<f64>()) {
+        (Ok(value), _) => Ok(value),
+        (_, Ok(value)) => float_coordinate(value, text),
+        _ => Err(ShapeErr::NotIntegral(text.to_string())),
+    }
+}
+
+/// Bring a ring read from outside into the form the rest of the crate
+/// expects: no closing point, no repeated or collinear points, clockwise, and
+/// starting at its lowest (then leftmost) point.
+pub fn normalize_ring(mut ring: Vec<Point>) -> Result<Vec<Point>, ShapeErr> {
+    ring.dedup();
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let n = ring.len();
+    for ix in 0..n {
+        let (p, q) = (ring[ix], ring[(ix + 1) % n]);
+        if p.x != q.x && p.y != q.y {
+            return Err(ShapeErr::NotRectilinear(p, q));
+        }
+    }
+
+    let mut ring = drop_collinear(ring);
+    if ring.len() < 4 || signed_area2(&ring) == Area2::ZERO {
+        return Err(ShapeErr::NotEnoughPoints);
+    }
+    if signed_area2(&ring) > Area2::ZERO {
+        ring.reverse();
+    }
+    start_at_min(&mut ring);
+    Ok(ring)
+}
+
+/// The rings of `outline` in the orientation GIS formats expect (RFC 7946):
+/// the outer ring counter-clockwise and holes clockwise, each closed by
+/// repeating its first point.
+pub(crate) fn gis_rings(outline: &Outline) -> Vec<Vec<Point>> {
+    let close = |mut ring: Vec<Point>| {
+        if let Some(&first) = ring.first() {
+            ring.push(first);
+        }
+        ring
+    };
+    let mut outer = outline.outer.clone();
+    outer.reverse();
+    std::iter::once(close(outer))
+        .chain(outline.holes.iter().cloned().map(close))
+        .collect()
+}
+
+/// A rect as a single outline.
+pub(crate) fn rect_outline(rect: &Rect) -> Outline {
+    Outline {
+        outer: vec![
+            Point::new(rect.x_min(), rect.y_min()),
+            Point::new(rect.x_min(), rect.y_max()),
+            Point::new(rect.x_max(), rect.y_max()),
+            Point::new(rect.x_max(), rect.y_min()),
+        ],
+        holes: Vec::new(),
+    }
+}
+
+impl Outline {
+    /// Build an outline from rings read from outside, normalizing each with
+    /// `normalize_ring`.
+    pub fn from_rings(
+        outer: Vec<Point>,
+        holes: Vec<Vec<Point>>,
+    ) -> Result<Outline, ShapeErr> {
+        Ok(Outline {
+            outer: normalize_ring(outer)?,
+            holes: holes
+                .into_iter()
+                .map(normalize_ring)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn rings_are_normalized() {
+        // Closed, counter-clockwise, with a repeated and a collinear point,
+        // and starting at the top.
+        let ring = normalize_ring(ring(&[
+            (2, 2),
+            (0, 2),
+            (0, 0),
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (2, 2),
+        ]));
+        assert_eq!(
+            ring,
+            Ok(vec![(0, 0), (0, 2), (2, 2), (2, 0)]
+                .into_iter()
+                .map(Point::from)
+                .collect())
+        );
+    }
+
+    #[test]
+    fn bad_rings() {
+        assert_eq!(
+            normalize_ring(ring(&[(0, 0), (0, 2), (2, 0)])),
+            Err(ShapeErr::NotRectilinear(Point::new(0, 2), Point::new(2, 0)))
+        );
+        assert_eq!(
+            normalize_ring(ring(&[(0, 0), (0, 2), (0, 1), (0, 0)])),
+            Err(ShapeErr::NotEnoughPoints)
+        );
+        assert_eq!(parse_coordinate("-3"), Ok(-3));
+        assert_eq!(parse_coordinate("4.0"), Ok(4));
+        assert_eq!(
+            parse_coordinate("4.5"),
+            Err(ShapeErr::NotIntegral("4.5".to_string()))
+        );
+        for text in ["inf", "NaN", "x"] {
+            assert_eq!(
+                parse_coordinate(text),
+                Err(ShapeErr::NotIntegral(text.to_string()))
+            );
+        }
+        assert_eq!(parse_coordinate("-9223372036854775808.0"), Ok(isize::MIN));
+        for text in ["1e30", "-1e30", "9223372036854775808"] {
+            assert_eq!(
+                parse_coordinate(text),
+                Err(ShapeErr::OutOfRange(text.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn gis_rings_are_closed_and_counter_clockwise() {
+        let outline = rect_outline(&Rect::from_bounds(0, 0, 2, 1));
+        assert_eq!(
+            gis_rings(&outline),
+            vec![ring(&[(2, 0), (2, 1), (0, 1), (0, 0), (2, 0)])]
+        );
+        assert!(signed_area2(&gis_rings(&outline)[0]) > Area2::ZERO);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    boolean::BooleanOp,
+    decomposer::{DecompErr, Decomposer},
+    point::Point,
+    provenance::{HorizontalBoundary, SideKind},
+    rect::Rect,
+};
+
+/// Why a rect thinner than the minimum dimension remains in the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliverKind {
+    /// The polygon itself is this narrow: both sides across the thin
+    /// dimension lie on its boundary.
+    Polygon,
+    /// At least one side across the thin dimension is a cut, but no choice
+    /// of cut direction around it avoided the sliver.
+    Cut,
+}
+
+/// A rect of the output with a width or height below the minimum dimension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sliver {
+    /// Index of the rect in `SliverDecomposition::rects`.
+    pub rect: usize,
+    /// The smaller of the rect's width and height.
+    pub thickness: isize,
+    pub kind: SliverKind,
+}
+
+/// A decomposition in which cut directions were chosen to avoid slivers.
+#[derive(Clone, Debug, Default)]
+pub struct SliverDecomposition {
+    pub rects: Vec<Rect>,
+    /// The slivers that could not be avoided.
+    pub slivers: Vec<Sliver>,
+}
+
+#[inline]
+fn is_sliver(rect: &Rect, min_dimension: isize) -> bool {
+    !rect.is_degenerate() && rect.width().min(rect.height()) < min_dimension
+}
+
+/// Re-cut a region (given as the rects covering it) with vertical cuts only,
+/// by sweeping its transpose with the horizontal scanline.
+pub(crate) fn cut_vertically(region: &[Rect]) -> Vec<Rect> {
+    let transposed = region.iter().map(Rect::transposed).collect::<Vec<Rect>>();
+    BooleanOp::Union
+        .apply(&transposed, &[])
+        .iter()
+        .map(Rect::transposed)
+        .collect()
+}
+
+fn find(parents: &mut [usize], ix: usize) -> usize {
+    let mut root = ix;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[ix] = root;
+    root
+}
+
+impl Decomposer {
+    /// Decompose a polygon so that, where possible, no rect is thinner than
+    /// `min_dimension`.
+    ///
+    /// The scanline only cuts horizontally, so it produces slivers wherever
+    /// two vertices have nearly equal y. Each such sliver is grouped with the
+    /// rects it shares a cut with, and the group is re-cut vertically if that
+    /// yields fewer slivers. Slivers that remain are reported rather than
+    /// silently returned.
+    pub fn decompose_sliver_aware(
+        points: Vec<Point>,
+        min_dimension: isize,
+    ) -> Result<SliverDecomposition, DecompErr> {
+        let horizontal = HorizontalBoundary::new(&points);
+        let vertical = HorizontalBoundary::new(
+            &points
+                .iter()
+                .map(|p| Point::new(p.y, p.x))
+                .collect::<Vec<Point>>(),
+        );
+        let graph = Self::decompose_graph(points)?;
+
+        // Group slivers caused by a cut with the rects on the other side of
+        // that cut.
+        let mut parents = (0..graph.rects.len()).collect::<Vec<usize>>();
+        let mut avoidable = vec![false; graph.rects.len()];
+        for (ix, decomposed) in graph.rects.iter().enumerate() {
+            let rect = decomposed.rect;
+            avoidable[ix] = !rect.is_degenerate()
+                && rect.height() < min_dimension
+                && (decomposed.top != SideKind::Boundary
+                    || decomposed.bottom != SideKind::Boundary);
+        }
+        for adjacency in graph.adjacencies.iter() {
+            if avoidable[adjacency.below] || avoidable[adjacency.above] {
+                let (a, b) = (
+                    find(&mut parents, adjacency.below),
+                    find(&mut parents, adjacency.above),
+                );
+                parents[a] = b;
+            }
+        }
+
+        let mut regions: BTreeMap<usize, Vec<Rect>> = BTreeMap::new();
+        for (ix, decomposed) in graph.rects.iter().enumerate() {
+            let root = find(&mut parents, ix);
+            regions.entry(root).or_default().push(decomposed.rect);
+        }
+
+        let sliver_kind = |rect: &Rect| -> Option<SliverKind> {
+            if !is_sliver(rect, min_dimension) {
+                return None;
+            }
+            let narrow_polygon = if rect.height() < min_dimension {
+                [rect.y_min(), rect.y_max()].iter().all(|&y| {
+                    horizontal.classify(y, rect.x_min(), rect.x_max())
+                        == SideKind::Boundary
+                })
+            } else {
+                [rect.x_min(), rect.x_max()].iter().all(|&x| {
+                    vertical.classify(x, rect.y_min(), rect.y_max())
+                        == SideKind::Boundary
+                })
+            };
+            Some(if narrow_polygon {
+                SliverKind::Polygon
+            } else {
+                SliverKind::Cut
+            })
+        };
+        // Slivers caused by cuts are the ones worth avoiding; the total count
+        // breaks ties.
+        let score = |rects: &[Rect]| {
+            rects.iter().fold((0, 0), |(cut, total), rect| {
+                match sliver_kind(rect) {
+                    Some(SliverKind::Cut) => (cut + 1, total + 1),
+                    Some(SliverKind::Polygon) => (cut, total + 1),
+                    None => (cut, total),
+                }
+            })
+        };
+
+        let mut rects = Vec::with_capacity(graph.rects.len());
+        for region in regions.into_values() {
+            let recut = cut_vertically(&region);
+            if region.len() > 1 && score(&recut) < score(&region) {
+                rects.extend(recut);
+            } else {
+                rects.extend(region);
+            }
+        }
+
+        let slivers = rects
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, rect)| {
+                sliver_kind(rect).map(|kind| Sliver {
+                    rect: ix,
+                    thickness: rect.width().min(rect.height()),
+                    kind,
+                })
+            })
+            .collect();
+
+        Ok(SliverDecomposition { rects, slivers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn cut_sliver_is_recut_vertically() {
+        let points =
+            ring(&[(0, 0), (0, 10), (10, 10), (10, 9), (20, 9), (20, 0)]);
+        // The scanline leaves a 1-high sliver on top of the wide rect.
+        assert!(Decomposer::decompose(points.clone())
+            .unwrap()
+            .contains(&Rect::from_bounds(0, 9, 10, 10)));
+
+        let decomposition =
+            Decomposer::decompose_sliver_aware(points, 3).unwrap();
+        let mut rects = decomposition.rects;
+        rects.sort_by_key(|rect| rect.x_min());
+        assert_eq!(
+            rects,
+            vec![
+                Rect::from_bounds(0, 0, 10, 10),
+                Rect::from_bounds(10, 0, 20, 9)
+            ]
+        );
+        assert!(decomposition.slivers.is_empty());
+    }
+
+    #[test]
+    fn polygon_sliver_is_reported() {
+        let points =
+            ring(&[(0, 0), (0, 10), (10, 10), (10, 1), (20, 1), (20, 0)]);
+        let decomposition =
+            Decomposer::decompose_sliver_aware(points, 3).unwrap();
+        assert_eq!(decomposition.slivers.len(), 1);
+        let sliver = decomposition.slivers[0];
+        assert_eq!(
+            decomposition.rects[sliver.rect],
+            Rect::from_bounds(10, 0, 20, 1)
+        );
+        assert_eq!(sliver.thickness, 1);
+        assert_eq!(sliver.kind, SliverKind::Polygon);
+    }
+}
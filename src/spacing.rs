@@ -0,0 +1,573 @@
+// Based on:
+// LEF/DEF 5.8 Language Reference, "Layer (Routing)": SPACINGTABLE
+// PARALLELRUNLENGTH
+
+use crate::{
+    cover::maximal_rects,
+    decomposer::{DecompErr, Decomposer},
+    geometry::Geometry,
+    grid::CompressedGrid,
+    outline::{drop_collinear, signed_area2, Area2, Interval},
+    point::Point,
+    rect::Rect,
+    rtree::RTree,
+};
+
+/// Minimum spacing indexed by wire width (rows) and parallel run length
+/// (columns), as in a LEF `SPACINGTABLE PARALLELRUNLENGTH`.
+///
+/// The row used is the last one whose width is exceeded by the actual width,
+/// and the column the last one whose length is exceeded by the actual run
+/// length, falling back to the first row and column.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpacingTable {
+    pub widths: Vec<isize>,
+    pub run_lengths: Vec<isize>,
+    /// `spacings[row][column]`, one row per width and one column per run
+    /// length.
+    pub spacings: Vec<Vec<isize>>,
+}
+
+impl SpacingTable {
+    /// A single spacing, whatever the width and run length.
+    pub fn uniform(spacing: isize) -> Self {
+        Self {
+            widths: vec![0],
+            run_lengths: vec![0],
+            spacings: vec![vec![spacing]],
+        }
+    }
+
+    pub fn spacing(&self, width: isize, run_length: isize) -> isize {
+        let row = self.widths.iter().rposition(|&w| width > w).unwrap_or(0);
+        let column = self
+            .run_lengths
+            .iter()
+            .rposition(|&l| run_length > l)
+            .unwrap_or(0);
+        self.spacings[row][column]
+    }
+
+    /// The largest spacing of the table: shapes further apart than this
+    /// never violate it.
+    pub fn max_spacing(&self) -> isize {
+        self.spacings.iter().flatten().copied().max().unwrap_or(0)
+    }
+}
+
+/// Two polygons closer than the spacing table allows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpacingViolation {
+    /// Indices of the two polygons, the lower first.
+    pub polygons: (usize, usize),
+    /// The empty region between the two facing edges (or corners).
+    pub marker: Rect,
+    /// The distance between the edges, or the euclidean distance between
+    /// the corners rounded down.
+    pub distance: isize,
+    /// The larger of the widths of the two polygons where they face each
+    /// other (see `Decomposer::check_spacing`).
+    pub width: isize,
+    /// Length over which the edges face each other, zero for corners.
+    pub run_length: isize,
+    pub required: isize,
+}
+
+/// A vertical boundary edge of a polygon, with collinear edges merged. The
+/// polygon lies left of it if it `faces_right`, and right of it otherwise.
+#[derive(Clone, Copy, Debug)]
+struct Wall {
+    polygon: usize,
+    x: isize,
+    lo: isize,
+    hi: isize,
+    faces_right: bool,
+}
+
+/// A convex vertex of a polygon, and the signs of the directions along
+/// which it looks out of the polygon.
+#[derive(Clone, Copy, Debug)]
+struct Corner {
+    polygon: usize,
+    at: Point,
+    opens: (isize, isize),
+}
+
+/// The boundaries of the polygons of a batch, along with the maximal rects
+/// of each, which together cover exactly the polygons.
+struct Boundaries {
+    walls: Vec<Wall>,
+    corners: Vec<Corner>,
+    material: RTree<usize>,
+}
+
+/// The ring without repeated, closing or collinear points, clockwise.
+fn clockwise(points: &[Point]) -> Vec<Point> {
+    let mut ring = points.to_vec();
+    ring.dedup();
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let mut ring = drop_collinear(ring);
+    if signed_area2(&ring) > Area2::ZERO {
+        ring.reverse();
+    }
+    ring
+}
+
+/// The vertical edges of a clockwise ring: going up, the polygon lies on
+/// their right.
+fn walls(polygon: usize, ring: &[Point]) -> impl Iterator<Item = Wall> + '_ {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .filter(|(p, q)| p.x == q.x)
+        .map(move |(p, q)| Wall {
+            polygon,
+            x: p.x,
+            lo: p.y.min(q.y),
+            hi: p.y.max(q.y),
+            faces_right: p.y > q.y,
+        })
+}
+
+/// The convex vertices of a clockwise ring, which turn right.
+fn corners(
+    polygon: usize,
+    ring: &[Point],
+) -> impl Iterator<Item = Corner> + '_ {
+    let n = ring.len();
+    (0..n).filter_map(move |ix| {
+        let (prev, at, next) =
+            (ring[(ix + n - 1) % n], ring[ix], ring[(ix + 1) % n]);
+        let (din, dout) = (
+            ((at.x - prev.x).signum(), (at.y - prev.y).signum()),
+            ((next.x - at.x).signum(), (next.y - at.y).signum()),
+        );
+        (din.0 * dout.1 - din.1 * dout.0 < 0).then_some(Corner {
+            polygon,
+            at,
+            opens: (din.0 - dout.0, din.1 - dout.1),
+        })
+    })
+}
+
+impl Boundaries {
+    /// The boundaries of clockwise `rings` with maximal rects `covers`, with
+    /// x and y swapped if `transpose`.
+    fn new(
+        rings: &[Vec<Point>],
+        covers: &[Vec<Rect>],
+        transpose: bool,
+    ) -> Self {
+        let flip = |p: &Point| {
+            if transpose {
+                Point::new(p.y, p.x)
+            } else {
+                *p
+            }
+        };
+        let mut boundaries = Boundaries {
+            walls: Vec::new(),
+            corners: Vec::new(),
+            material: RTree::default(),
+        };
+        let mut material = Vec::new();
+        for (id, (ring, cover)) in rings.iter().zip(covers).enumerate() {
+            // Transposing mirrors the ring, which then turns the other way.
+            let mut ring = ring.iter().map(flip).collect::<Vec<Point>>();
+            if transpose {
+                ring.reverse();
+            }
+            boundaries.walls.extend(walls(id, &ring));
+            boundaries.corners.extend(corners(id, &ring));
+            material.extend(cover.iter().map(|rect| {
+                (if transpose { rect.transposed() } else { *rect }, id)
+            }));
+        }
+        boundaries.material = RTree::bulk_load(material);
+        boundaries
+    }
+
+    /// The width of `polygon` along `x` over `[lo, hi]`, on its left if
+    /// `left`: the smaller side of the widest of its maximal rects with a
+    /// side there.
+    fn width(
+        &self,
+        polygon: usize,
+        x: isize,
+        lo: isize,
+        hi: isize,
+        left: bool,
+    ) -> isize {
+        self.material
+            .window(&Rect::from_bounds(x, lo, x, hi))
+            .into_iter()
+            .filter(|(rect, id)| {
+                *id == polygon
+                    && if left { rect.x_max() } else { rect.x_min() } == x
+                    && (lo == hi || (rect.y_min() < hi && lo < rect.y_max()))
+            })
+            .map(|(rect, _)| rect.width().min(rect.height()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The violations between walls of different polygons facing each
+    /// other horizontally.
+    fn facing_walls(&self, table: &SpacingTable) -> Vec<SpacingViolation> {
+        let reach = table.max_spacing();
+        let tree = RTree::bulk_load(
+            (0..self.walls.len())
+                .map(|ix| {
+                    let wall = &self.walls[ix];
+                    (Rect::from_bounds(wall.x, wall.lo, wall.x, wall.hi), ix)
+                })
+                .collect(),
+        );
+        let mut violations = Vec::new();
+        for a in self.walls.iter().filter(|wall| wall.faces_right) {
+            let window = Rect::from_bounds(a.x, a.lo, a.x + reach, a.hi);
+            for (_, ix) in tree.window(&window) {
+                let b = &self.walls[*ix];
+                if b.faces_right || b.polygon == a.polygon || b.x <= a.x {
+                    continue;
+                }
+                let (lo, hi) = (a.lo.max(b.lo), a.hi.min(b.hi));
+                for (start, end) in
+                    unshielded_runs(&self.material, a.x, b.x, lo, hi)
+                {
+                    let width = self
+                        .width(a.polygon, a.x, start, end, true)
+                        .max(self.width(b.polygon, b.x, start, end, false));
+                    let (distance, run_length) = (b.x - a.x, end - start);
+                    let required = table.spacing(width, run_length);
+                    if distance < required {
+                        violations.push(SpacingViolation {
+                            polygons: (
+                                a.polygon.min(b.polygon),
+                                a.polygon.max(b.polygon),
+                            ),
+                            marker: Rect::from_bounds(a.x, start, b.x, end),
+                            distance,
+                            width,
+                            run_length,
+                            required,
+                        });
+                    }
+                }
+            }
+        }
+        violations
+    }
+
+    /// The violations between convex corners of different polygons looking
+    /// at each other diagonally.
+    fn facing_corners(&self, table: &SpacingTable) -> Vec<SpacingViolation> {
+        let reach = table.max_spacing();
+        let tree = RTree::bulk_load(
+            (0..self.corners.len())
+                .map(|ix| {
+                    let at = self.corners[ix].at;
+                    (Rect::new(at, at), ix)
+                })
+                .collect(),
+        );
+        let corner_width = |corner: &Corner| {
+            self.material
+                .at_point(corner.at)
+                .into_iter()
+                .filter(|(_, id)| *id == corner.polygon)
+                .map(|(rect, _)| rect.width().min(rect.height()))
+                .max()
+                .unwrap_or(0)
+        };
+        let mut violations = Vec::new();
+        for a in &self.corners {
+            let window = Rect::from_bounds(
+                a.at.x - reach,
+                a.at.y - reach,
+                a.at.x + reach,
+                a.at.y + reach,
+            );
+            for (_, ix) in tree.window(&window) {
+                let b = &self.corners[*ix];
+                let (dx, dy) = (b.at.x - a.at.x, b.at.y - a.at.y);
+                if b.polygon <= a.polygon
+                    || dx == 0
+                    || dy == 0
+                    || a.opens != (dx.signum(), dy.signum())
+                    || b.opens != (-dx.signum(), -dy.signum())
+                {
+                    continue;
+                }
+                let marker = Rect::from_bounds(a.at.x, a.at.y, b.at.x, b.at.y);
+                let shielded =
+                    self.material.window(&marker).iter().any(|(rect, _)| {
+                        blocks(
+                            rect,
+                            marker.x_min(),
+                            marker.x_max(),
+                            marker.y_min(),
+                            marker.y_max(),
+                        )
+                    });
+                let distance = (dx * dx + dy * dy).isqrt();
+                let width = corner_width(a).max(corner_width(b));
+                let required = table.spacing(width, 0);
+                if !shielded && distance < required {
+                    violations.push(SpacingViolation {
+                        polygons: (a.polygon, b.polygon),
+                        marker,
+                        distance,
+                        width,
+                        run_length: 0,
+                        required,
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Whether the interiors of `rect` and of the box `[x0, x1] x [y0, y1]`
+/// overlap. For a flat box, whether `rect` straddles it.
+#[inline]
+fn blocks(rect: &Rect, x0: isize, x1: isize, y0: isize, y1: isize) -> bool {
+    rect.x_min() < x1
+        && x0 < rect.x_max()
+        && rect.y_min() < y1
+        && y0 < rect.y_max()
+}
+
+/// The stretches of `[lo, hi]` along which two walls at `x0` and `x1` see
+/// each other, i.e. are not shielded by any rect of `tree`.
+fn unshielded_runs<T>(
+    tree: &RTree<T>,
+    x0: isize,
+    x1: isize,
+    lo: isize,
+    hi: isize,
+) -> Vec<Interval> {
+    if lo > hi {
+        return Vec::new();
+    }
+    let mut shields = tree
+        .window(&Rect::from_bounds(x0, lo, x1, hi))
+        .into_iter()
+        .filter(|(rect, _)| blocks(rect, x0, x1, lo, hi))
+        .map(|(rect, _)| (rect.y_min().max(lo), rect.y_max().min(hi)))
+        .collect::<Vec<Interval>>();
+    if lo == hi {
+        return if shields.is_empty() {
+            vec![(lo, hi)]
+        } else {
+            vec![]
+        };
+    }
+    shields.sort_unstable();
+    let mut runs = Vec::new();
+    let mut cursor = lo;
+    for (start, end) in shields {
+        if start > cursor {
+            runs.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < hi {
+        runs.push((cursor, hi));
+    }
+    runs
+}
+
+impl Decomposer {
+    /// Check the spacing between every pair of polygons (given as points in
+    /// either winding) against `table`.
+    ///
+    /// Spacing is measured between the boundary edges of the polygons, with
+    /// collinear edges merged, so the run length of two facing edges is not
+    /// cut short where a decomposition would split them. Edges of different
+    /// polygons within the table's largest spacing of each other are
+    /// reported along every stretch where no polygon lies between them;
+    /// convex corners looking at each other diagonally are checked corner to
+    /// corner, with a run length of zero. Overlapping or touching polygons
+    /// are not reported.
+    ///
+    /// The width of a polygon where it faces another is the smaller side of
+    /// its widest maximal rect (see `Decomposer::cover`) with a side on the
+    /// facing stretch of edge, or containing the facing corner.
+    pub fn check_spacing(
+        polygons: &[Vec<Point>],
+        table: &SpacingTable,
+    ) -> Result<Vec<SpacingViolation>, DecompErr> {
+        let mut rings = Vec::with_capacity(polygons.len());
+        let mut covers = Vec::with_capacity(polygons.len());
+        for points in polygons {
+            let ring = clockwise(points);
+            let geometry = Geometry::new(ring.clone())?;
+            covers.push(maximal_rects(&CompressedGrid::from_geometry(
+                &geometry,
+                &[],
+            )));
+            rings.push(ring);
+        }
+
+        let boundaries = Boundaries::new(&rings, &covers, false);
+        let mut violations = boundaries.facing_walls(table);
+        violations.extend(boundaries.facing_corners(table));
+        let transposed = Boundaries::new(&rings, &covers, true);
+        violations.extend(transposed.facing_walls(table).into_iter().map(
+            |violation| SpacingViolation {
+                marker: violation.marker.transposed(),
+                ..violation
+            },
+        ));
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(isize, isize)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    fn rect(x0: isize, y0: isize, x1: isize, y1: isize) -> Vec<Point> {
+        ring(&[(x0, y0), (x0, y1), (x1, y1), (x1, y0)])
+    }
+
+    #[test]
+    fn run_length_spans_scanline_cuts() {
+        // The right wall at x = 10 is one edge, although the decomposition
+        // cuts it at y = 50.
+        let l =
+            ring(&[(-20, 0), (-20, 50), (0, 50), (0, 100), (10, 100), (10, 0)]);
+        let table = SpacingTable {
+            widths: vec![0],
+            run_lengths: vec![0, 60],
+            spacings: vec![vec![3, 8]],
+        };
+        let violations =
+            Decomposer::check_spacing(&[l, rect(15, 0, 25, 100)], &table)
+                .unwrap();
+        assert_eq!(
+            violations,
+            vec![SpacingViolation {
+                polygons: (0, 1),
+                marker: Rect::from_bounds(10, 0, 15, 100),
+                distance: 5,
+                width: 30,
+                run_length: 100,
+                required: 8,
+            }]
+        );
+    }
+
+    #[test]
+    fn width_is_measured_on_the_polygon() {
+        // A notch in the left side slices the decomposition into a 2-high
+        // rect across the whole square, but the square is 35 wide there.
+        let notched = ring(&[
+            (0, 0),
+            (0, 10),
+            (5, 10),
+            (5, 12),
+            (0, 12),
+            (0, 40),
+            (40, 40),
+            (40, 0),
+        ]);
+        let table = SpacingTable {
+            widths: vec![0, 20],
+            run_lengths: vec![0],
+            spacings: vec![vec![3], vec![6]],
+        };
+        let violations =
+            Decomposer::check_spacing(&[notched, rect(45, 10, 47, 12)], &table)
+                .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].width, 35);
+        assert_eq!(violations[0].run_length, 2);
+    }
+
+    #[test]
+    fn shielded_runs_are_split() {
+        let polygons = [rect(0, 0, 2, 10), rect(6, 0, 8, 10), rect(3, 4, 5, 6)];
+        let violations =
+            Decomposer::check_spacing(&polygons, &SpacingTable::uniform(5))
+                .unwrap();
+        let mut found = violations
+            .iter()
+            .map(|v| (v.polygons, v.marker, v.distance, v.run_length))
+            .collect::<Vec<_>>();
+        found.sort_unstable_by_key(|&(polygons, marker, ..)| {
+            (polygons, marker.y_min(), marker.x_min())
+        });
+        assert_eq!(
+            found,
+            [
+                ((0, 1), Rect::from_bounds(2, 0, 6, 4), 4, 4),
+                ((0, 1), Rect::from_bounds(2, 6, 6, 10), 4, 4),
+                ((0, 2), Rect::from_bounds(2, 4, 3, 6), 1, 2),
+                ((1, 2), Rect::from_bounds(5, 4, 6, 6), 1, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn vertical_spacing_and_corners() {
+        let table = SpacingTable::uniform(4);
+        let stacked = [rect(0, 0, 10, 2), rect(0, 5, 10, 7)];
+        let violations = Decomposer::check_spacing(&stacked, &table).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].marker, Rect::from_bounds(0, 2, 10, 5));
+        assert_eq!((violations[0].distance, violations[0].run_length), (3, 10));
+
+        // Either winding is accepted.
+        let mut diagonal = [rect(0, 0, 2, 2), rect(4, 5, 6, 7)];
+        diagonal[0].reverse();
+        let violations = Decomposer::check_spacing(&diagonal, &table).unwrap();
+        assert_eq!(
+            violations,
+            vec![SpacingViolation {
+                polygons: (0, 1),
+                marker: Rect::from_bounds(2, 2, 4, 5),
+                distance: 3,
+                width: 2,
+                run_length: 0,
+                required: 4,
+            }]
+        );
+        assert!(Decomposer::check_spacing(
+            &diagonal,
+            &SpacingTable::uniform(3)
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn touching_polygons_are_not_reported() {
+        let polygons = [rect(0, 0, 2, 2), rect(2, 0, 4, 2), rect(3, 1, 5, 3)];
+        assert_eq!(
+            Decomposer::check_spacing(&polygons, &SpacingTable::uniform(10)),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn table_lookup() {
+        let table = SpacingTable {
+            widths: vec![0, 10],
+            run_lengths: vec![0, 50],
+            spacings: vec![vec![2, 3], vec![4, 6]],
+        };
+        assert_eq!(table.spacing(5, 20), 2);
+        assert_eq!(table.spacing(5, 51), 3);
+        assert_eq!(table.spacing(11, 50), 4);
+        assert_eq!(table.spacing(11, 60), 6);
+        assert_eq!(table.max_spacing(), 6);
+    }
+}
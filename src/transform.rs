@@ -0,0 +1,193 @@
+// Based on:
+// https://github.com/The-OpenROAD-Project/OpenROAD/blob/master/src/odb/src/db/dbTransform.cpp
+
+use crate::{point::Point, rect::Rect};
+
+/// The eight DEF instance orientations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// No rotation (R0).
+    #[default]
+    N,
+    /// Rotated by 180 degrees (R180).
+    S,
+    /// Rotated counter-clockwise by 270 degrees (R270).
+    E,
+    /// Rotated counter-clockwise by 90 degrees (R90).
+    W,
+    /// Mirrored about the y axis (MY).
+    FN,
+    /// Mirrored about the x axis (MX).
+    FS,
+    /// Mirrored about the y axis, then rotated by 90 degrees (MYR90).
+    FE,
+    /// Mirrored about the x axis, then rotated by 90 degrees (MXR90).
+    FW,
+}
+
+impl Orientation {
+    /// Mirrored orientations flip the winding of a polygon.
+    #[inline]
+    pub fn is_mirrored(self) -> bool {
+        matches!(
+            self,
+            Orientation::FN
+                | Orientation::FS
+                | Orientation::FE
+                | Orientation::FW
+        )
+    }
+
+    /// Rotate/mirror a point about the origin.
+    #[inline]
+    pub fn apply(self, p: Point) -> Point {
+        let (x, y) = (p.x, p.y);
+        match self {
+            Orientation::N => Point::new(x, y),
+            Orientation::S => Point::new(-x, -y),
+            Orientation::E => Point::new(y, -x),
+            Orientation::W => Point::new(-y, x),
+            Orientation::FN => Point::new(-x, y),
+            Orientation::FS => Point::new(x, -y),
+            Orientation::FE => Point::new(-y, -x),
+            Orientation::FW => Point::new(y, x),
+        }
+    }
+}
+
+/// A placement transform: points are first scaled, then oriented about the
+/// origin, then translated by `offset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transform {
+    pub orientation: Orientation,
+    pub offset: Point,
+    pub scale: isize,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            orientation: Orientation::N,
+            offset: Point::default(),
+            scale: 1,
+        }
+    }
+}
+
+impl Transform {
+    pub fn new(orientation: Orientation, offset: Point) -> Self {
+        Self {
+            orientation,
+            offset,
+            scale: 1,
+        }
+    }
+
+    #[inline]
+    pub fn with_scale(self, scale: isize) -> Self {
+        Self { scale, ..self }
+    }
+
+    #[inline]
+    pub fn apply_point(&self, p: Point) -> Point {
+        let oriented = self
+            .orientation
+            .apply(Point::new(p.x * self.scale, p.y * self.scale));
+        Point::new(oriented.x + self.offset.x, oriented.y + self.offset.y)
+    }
+
+    #[inline]
+    pub fn apply_rect(&self, rect: &Rect) -> Rect {
+        let (a, b) = (
+            self.apply_point(rect.left()),
+            self.apply_point(rect.right()),
+        );
+        Rect::from_bounds(a.x, a.y, b.x, b.y)
+    }
+
+    /// Transform the points of a polygon. Mirroring reverses the winding, so
+    /// the points of a mirrored polygon are reversed as well, to keep a
+    /// clockwise polygon clockwise for `Geometry::new`.
+    pub fn apply_polygon(&self, points: &[Point]) -> Vec<Point> {
+        let mut transformed = points
+            .iter()
+            .map(|&p| self.apply_point(p))
+            .collect::<Vec<Point>>();
+        if self.orientation.is_mirrored() {
+            transformed.reverse();
+        }
+        transformed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polygon::{Polygon, Winding};
+
+    const ALL: [Orientation; 8] = [
+        Orientation::N,
+        Orientation::S,
+        Orientation::E,
+        Orientation::W,
+        Orientation::FN,
+        Orientation::FS,
+        Orientation::FE,
+        Orientation::FW,
+    ];
+
+    #[test]
+    fn orientations() {
+        let images = ALL.map(|o| o.apply(Point::new(2, 1)));
+        assert_eq!(
+            images,
+            [
+                (2, 1),
+                (-2, -1),
+                (1, -2),
+                (-1, 2),
+                (-2, 1),
+                (2, -1),
+                (-1, -2),
+                (1, 2)
+            ]
+            .map(Point::from)
+        );
+        // The flipped orientations are the mirror, then the rotation.
+        for (mirror, rotated) in [
+            (Orientation::FN, Orientation::FE),
+            (Orientation::FS, Orientation::FW),
+        ] {
+            let p = Point::new(2, 1);
+            assert_eq!(Orientation::W.apply(mirror.apply(p)), rotated.apply(p));
+        }
+    }
+
+    #[test]
+    fn scale_orient_then_translate() {
+        let transform =
+            Transform::new(Orientation::W, Point::new(10, 20)).with_scale(3);
+        assert_eq!(transform.apply_point(Point::new(2, 1)), Point::new(7, 26));
+        assert_eq!(
+            transform.apply_rect(&Rect::from_bounds(0, 0, 2, 1)),
+            Rect::from_bounds(7, 20, 10, 26)
+        );
+        assert_eq!(
+            Transform::default().apply_point(Point::new(2, 1)),
+            Point::new(2, 1)
+        );
+    }
+
+    #[test]
+    fn polygons_stay_clockwise() {
+        let l = [(0, 0), (0, 2), (1, 2), (1, 1), (2, 1), (2, 0)]
+            .map(Point::from)
+            .to_vec();
+        for orientation in ALL {
+            let transform = Transform::new(orientation, Point::new(-5, 7));
+            let polygon = Polygon::new(transform.apply_polygon(&l));
+            assert_eq!(polygon.orientation(), Some(Winding::Clockwise));
+            assert_eq!(polygon.area(), Some(3));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    decomposer::{DecompErr, Decomposer},
+    geometry::Geometry,
+    point::Point,
+    rect::Rect,
+    transform::{Orientation, Transform},
+};
+
+/// The direction along which a distance is measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// Between vertical edges.
+    Horizontal,
+    /// Between horizontal edges.
+    Vertical,
+}
+
+/// A region of a polygon narrower than the width rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WidthViolation {
+    pub marker: Rect,
+    /// Distance between the two boundary edges facing each other across
+    /// `marker`.
+    pub width: isize,
+    pub axis: Axis,
+}
+
+/// Swaps x and y. Being a mirror, it also keeps transformed polygons
+/// clockwise, and it is its own inverse.
+const SWAP_AXES: Transform = Transform {
+    orientation: Orientation::FW,
+    offset: Point { x: 0, y: 0 },
+    scale: 1,
+};
+
+/// The rects of a decomposition narrower than `min_width`, measured across
+/// their vertical sides.
+fn narrow(
+    rects: Vec<Rect>,
+    min_width: isize,
+    axis: Axis,
+) -> Vec<WidthViolation> {
+    rects
+        .into_iter()
+        .filter(|rect| !rect.is_degenerate() && rect.width() < min_width)
+        .map(|rect| WidthViolation {
+            marker: rect,
+            width: rect.width(),
+            axis,
+        })
+        .collect()
+}
+
+impl Decomposer {
+    /// Find every region of a polygon where two boundary edges facing each
+    /// other across its interior are closer than `min_width`.
+    ///
+    /// The rects of the (horizontal) decomposition have their vertical sides
+    /// on the polygon's boundary, so their widths are exactly the horizontal
+    /// widths of the polygon. Their tops and bottoms may be cuts though, so
+    /// their heights are not widths: vertical widths are instead read from
+    /// the decomposition of the polygon with its axes swapped. The polygon
+    /// is read back from `geometry` without the nodes `split_edge` adds, so a
+    /// geometry that has already been through the scanline can be checked.
+    ///
+    /// Widths are measured along the axes only: corner-to-corner distances
+    /// are not checked.
+    pub fn check_min_width(
+        geometry: &Geometry,
+        min_width: isize,
+    ) -> Result<Vec<WidthViolation>, DecompErr> {
+        let points = geometry.boundary();
+        let swapped = Decomposer::decompose(SWAP_AXES.apply_polygon(&points))?;
+        let mut violations =
+            narrow(Decomposer::decompose(points)?, min_width, Axis::Horizontal);
+        violations.extend(
+            narrow(swapped, min_width, Axis::Vertical).into_iter().map(
+                |violation| WidthViolation {
+                    marker: SWAP_AXES.apply_rect(&violation.marker),
+                    ..violation
+                },
+            ),
+        );
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An L with a 2-wide arm and a 3-high foot.
+    fn l_shape() -> Geometry {
+        let points = [(0, 0), (0, 10), (2, 10), (2, 3), (20, 3), (20, 0)]
+            .iter()
+            .map(|&p| p.into())
+            .collect();
+        Geometry::new(points).unwrap()
+    }
+
+    #[test]
+    fn both_axes() {
+        let geometry = l_shape();
+        assert_eq!(Decomposer::check_min_width(&geometry, 2), Ok(Vec::new()));
+        let arm = WidthViolation {
+            marker: Rect::from_bounds(0, 3, 2, 10),
+            width: 2,
+            axis: Axis::Horizontal,
+        };
+        assert_eq!(Decomposer::check_min_width(&geometry, 3), Ok(vec![arm]));
+        assert_eq!(
+            Decomposer::check_min_width(&geometry, 4),
+            Ok(vec![
+                arm,
+                WidthViolation {
+                    marker: Rect::from_bounds(2, 0, 20, 3),
+                    width: 3,
+                    axis: Axis::Vertical,
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn swap_is_its_own_inverse() {
+        let rect = Rect::from_bounds(1, 2, 5, 9);
+        assert_eq!(SWAP_AXES.apply_rect(&rect), Rect::from_bounds(2, 1, 9, 5));
+        assert_eq!(SWAP_AXES.apply_rect(&SWAP_AXES.apply_rect(&rect)), rect);
+    }
+}
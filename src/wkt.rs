@@ -0,0 +1,220 @@
+// Based on:
+// https://www.ogc.org/standard/sfa/ (Simple Features, section 7: Well-known
+// Text Representation for Geometry)
+
+use crate::{
+    outline::Outline,
+    point::Point,
+    rect::Rect,
+    shape::{gis_rings, parse_coordinate, rect_outline, ShapeErr},
+};
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn error(&self, expected: &str) -> ShapeErr {
+        ShapeErr::Syntax(format!(
+            "expected {} at offset {}",
+            expected, self.pos
+        ))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ShapeErr> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", c)))
+        }
+    }
+
+    /// The next run of characters up to whitespace or punctuation.
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "(),".contains(c))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Consume `EMPTY` if it comes next.
+    fn empty(&mut self) -> bool {
+        let start = self.pos;
+        if self.word().eq_ignore_ascii_case("EMPTY") {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    /// A parenthesized, comma separated list of items.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ShapeErr>,
+    ) -> Result<Vec<T>, ShapeErr> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(',') {
+            self.expect(',')?;
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    fn point(&mut self) -> Result<Point, ShapeErr> {
+        let x = parse_coordinate(self.word())?;
+        let y = parse_coordinate(self.word())?;
+        match self.peek() {
+            Some(',' | ')') => Ok(Point::new(x, y)),
+            _ => Err(self.error("two coordinates per point")),
+        }
+    }
+
+    fn polygon(&mut self) -> Result<Outline, ShapeErr> {
+        let mut rings = self.list(|parser| parser.list(Self::point))?;
+        let outer = rings.remove(0);
+        Outline::from_rings(outer, rings)
+    }
+
+    fn finish(&mut self) -> Result<(), ShapeErr> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("end of input")),
+        }
+    }
+}
+
+/// Read a WKT `POLYGON` or `MULTIPOLYGON` into one outline per polygon, with
+/// each ring normalized by `normalize_ring`.
+pub fn read(text: &str) -> Result<Vec<Outline>, ShapeErr> {
+    let mut parser = Parser { text, pos: 0 };
+    let tag = parser.word().to_ascii_uppercase();
+    let outlines = match tag.as_str() {
+        "POLYGON" | "MULTIPOLYGON" if parser.empty() => Vec::new(),
+        "POLYGON" => vec![parser.polygon()?],
+        "MULTIPOLYGON" => parser.list(Parser::polygon)?,
+        _ => return Err(ShapeErr::Unsupported(tag)),
+    };
+    parser.finish()?;
+    Ok(outlines)
+}
+
+fn write_rings(outline: &Outline) -> String {
+    let rings = gis_rings(outline)
+        .iter()
+        .map(|ring| {
+            let points = ring
+                .iter()
+                .map(|p| format!("{} {}", p.x, p.y))
+                .collect::<Vec<String>>();
+            format!("({})", points.join(", "))
+        })
+        .collect::<Vec<String>>();
+    format!("({})", rings.join(", "))
+}
+
+/// Write an outline as a WKT `POLYGON`, with its outer ring counter-clockwise
+/// and its holes clockwise, as in GeoJSON.
+pub fn write_polygon(outline: &Outline) -> String {
+    format!("POLYGON {}", write_rings(outline))
+}
+
+/// Write outlines as a WKT `MULTIPOLYGON`.
+pub fn write_multipolygon(outlines: &[Outline]) -> String {
+    if outlines.is_empty() {
+        return "MULTIPOLYGON EMPTY".to_string();
+    }
+    let polygons = outlines.iter().map(write_rings).collect::<Vec<String>>();
+    format!("MULTIPOLYGON ({})", polygons.join(", "))
+}
+
+/// Write a decomposition as a WKT `MULTIPOLYGON` with one polygon per rect.
+pub fn write_rects(rects: &[Rect]) -> String {
+    write_multipolygon(&rects.iter().map(rect_outline).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: &str =
+        "POLYGON ((3 0, 3 3, 0 3, 0 0, 3 0), (1 1, 1 2, 2 2, 2 1, 1 1))";
+
+    #[test]
+    fn frame_round_trips() {
+        let outlines = read(FRAME).unwrap();
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].holes.len(), 1);
+        assert_eq!(outlines[0].area(), Some(8));
+        assert_eq!(write_polygon(&outlines[0]), FRAME);
+        assert_eq!(read(&write_polygon(&outlines[0])).unwrap(), outlines);
+    }
+
+    #[test]
+    fn multipolygons() {
+        let rects = [
+            Rect::from_bounds(0, 0, 1, 1),
+            Rect::from_bounds(-2, 3, 4, 5),
+        ];
+        let text = write_rects(&rects);
+        assert_eq!(
+            text,
+            "MULTIPOLYGON (((1 0, 1 1, 0 1, 0 0, 1 0)), ((4 3, 4 5, -2 5, -2 3, 4 3)))"
+        );
+        let outlines = read(&text.to_lowercase()).unwrap();
+        assert_eq!(
+            outlines
+                .iter()
+                .map(Outline::area)
+                .collect::<Option<Vec<i128>>>(),
+            Some(vec![1, 12])
+        );
+        assert_eq!(read("MULTIPOLYGON EMPTY").unwrap(), Vec::new());
+        assert_eq!(write_multipolygon(&[]), "MULTIPOLYGON EMPTY");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            read("POINT (0 0)"),
+            Err(ShapeErr::Unsupported("POINT".to_string()))
+        );
+        assert!(matches!(
+            read("POLYGON ((0 0, 1 0)"),
+            Err(ShapeErr::Syntax(_))
+        ));
+        assert!(matches!(
+            read("POLYGON ((0 0, 1 0, 1 1, 0 1, 0 0)) x"),
+            Err(ShapeErr::Syntax(_))
+        ));
+        assert!(matches!(
+            read("POLYGON ((0 0, 1 0 5, 1 1, 0 1, 0 0))"),
+            Err(ShapeErr::Syntax(_))
+        ));
+        assert_eq!(
+            read("POLYGON ((0 0, 1.5 0, 1.5 1, 0 1, 0 0))"),
+            Err(ShapeErr::NotIntegral("1.5".to_string()))
+        );
+        assert_eq!(
+            read("POLYGON ((0 0, 0 1e30, 1 1e30, 1 0, 0 0))"),
+            Err(ShapeErr::OutOfRange("1e30".to_string()))
+        );
+    }
+}